@@ -6,6 +6,7 @@ uniform b_VsLocals {
   mat4 u_Model;
   mat4 u_View;
   mat4 u_Proj;
+  mat4 u_Camera;
 };
 
 uniform b_BulletPosition {
@@ -21,5 +22,5 @@ void main() {
                       sin(a_rotation),  cos(a_rotation),   0.0,
                       0.0,              0.0,               1.0) * a_Pos;
 
-  gl_Position = vec4(a_position, 0.0, 0.0) + vec4(rot_pos, 1.0) * u_Proj * u_View * u_Model;
+  gl_Position = vec4((u_Camera * vec4(a_position, 0.0, 1.0)).xy, 0.0, 0.0) + vec4(rot_pos, 1.0) * u_Proj * u_View * u_Model;
 }
@@ -0,0 +1,34 @@
+#version 150 core
+
+in vec2 v_BufPos;
+out vec4 Target0;
+
+layout (std140) uniform b_CharacterSprite {
+  vec4 a_uv;
+  vec4 a_trim;
+  vec2 a_offset;
+  float a_rotated;
+  float a_alpha;
+};
+
+uniform sampler2D t_CharacterSheet;
+
+void main() {
+  vec2 frame = (v_BufPos - a_trim.xy) / a_trim.zw;
+  if (any(lessThan(frame, vec2(0.0))) || any(greaterThan(frame, vec2(1.0)))) {
+    discard;
+  }
+  if (a_rotated > 0.5) {
+    frame = vec2(1.0 - frame.y, frame.x);
+  }
+
+  vec4 tex = texture(t_CharacterSheet, a_uv.xy + frame * a_uv.zw).rgba;
+  if(tex.a < 0.1) {
+    discard;
+  }
+  tex.r = smoothstep(0.1, 1.0, tex.r);
+  tex.g = smoothstep(0.1, 1.0, tex.g);
+  tex.b = smoothstep(0.1, 1.0, tex.b);
+  tex.a *= a_alpha;
+  Target0 = tex;
+}
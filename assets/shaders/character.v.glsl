@@ -0,0 +1,31 @@
+#version 150 core
+
+in vec3 a_Pos;
+in vec2 a_BufPos;
+out vec2 v_BufPos;
+
+uniform b_VsLocals {
+  mat4 u_Model;
+  mat4 u_View;
+  mat4 u_Proj;
+  mat4 u_Camera;
+};
+
+layout (std140) uniform b_CharacterSprite {
+  vec4 a_uv;
+  vec4 a_trim;
+  vec2 a_offset;
+  float a_rotated;
+  float a_alpha;
+};
+
+uniform b_CharacterPosition {
+  vec2 a_position;
+};
+
+void main() {
+  v_BufPos = a_BufPos;
+
+  vec4 pos = vec4(a_Pos.xy + a_offset, a_Pos.z, 1.0);
+  gl_Position = vec4((u_Camera * vec4(a_position, 0.0, 1.0)).xy, 0.0, 0.0) + u_Proj * u_View * u_Model * pos;
+}
@@ -3,22 +3,14 @@
 in vec2 v_BufPos;
 out vec4 Target0;
 
-struct TileMapData {
-  vec4 data;
-};
-
-const int TILEMAP_BUF_LENGTH = 4096;
-
-layout (std140) uniform b_TileMap {
-  TileMapData u_Data[TILEMAP_BUF_LENGTH];
-};
-
 layout (std140) uniform b_PsLocals {
   vec2 u_WorldSize;
   vec2 u_TilesheetSize;
+  float u_Opacity;
 };
 
 uniform sampler2D t_TileSheet;
+uniform sampler2D t_TileMap;
 
 uniform b_TimeModulo {
   float a_time;
@@ -41,22 +33,15 @@ void main() {
   vec2 bufTileCoords = floor(v_BufPos);
   vec2 rawUvOffsets = vec2(v_BufPos.x - bufTileCoords.x, 1.0 - (v_BufPos.y - bufTileCoords.y));
 
-  int bufIdx = int((bufTileCoords.y * u_WorldSize.x) + bufTileCoords.x);
-  vec4 entry = u_Data[bufIdx].data;
-  vec2 coords = vec2(0.0, 0.0);
-
-  if (bufIdx < TILEMAP_BUF_LENGTH) {
-    coords = vec2(mod(entry.x, u_TilesheetSize.y), floor(entry.x / u_TilesheetSize.x));
-  } else if (bufIdx < (TILEMAP_BUF_LENGTH * 2)) {
-    entry = u_Data[bufIdx - TILEMAP_BUF_LENGTH].data;
-    coords = vec2(mod(entry.y, u_TilesheetSize.y), floor(entry.y / u_TilesheetSize.x));
-  } else if (bufIdx < (TILEMAP_BUF_LENGTH * 3)) {
-    entry = u_Data[bufIdx - TILEMAP_BUF_LENGTH * 2].data;
-    coords = vec2(mod(entry.z, u_TilesheetSize.y), floor(entry.z / u_TilesheetSize.x));
-  } else {
-    entry = u_Data[bufIdx-TILEMAP_BUF_LENGTH * 3].data;
-    coords = vec2(mod(entry.w, u_TilesheetSize.y), floor(entry.w / u_TilesheetSize.x));
+  if (any(lessThan(bufTileCoords, vec2(0.0))) || any(greaterThanEqual(bufTileCoords, u_WorldSize))) {
+    discard;
+  }
+  float tile = texelFetch(t_TileMap, ivec2(bufTileCoords), 0).r;
+  // Empty cells are negative, the layer below shows through
+  if (tile < 0.0) {
+    discard;
   }
+  vec2 coords = vec2(mod(tile, u_TilesheetSize.x), floor(tile / u_TilesheetSize.x));
   vec2 uvCoords = (coords.xy + rawUvOffsets) / u_TilesheetSize.xy;
 
   vec3 norm = normalize(Normal);
@@ -70,5 +55,5 @@ void main() {
   if(tex.a < 0.1) {
     discard;
   }
-  Target0 = tex;
+  Target0 = vec4(tex.rgb, tex.a * u_Opacity);
 }
@@ -8,6 +8,7 @@ uniform b_VsLocals {
   mat4 u_Model;
   mat4 u_View;
   mat4 u_Proj;
+  mat4 u_Camera;
 };
 
 uniform b_TileMapPosition {
@@ -16,5 +17,5 @@ uniform b_TileMapPosition {
 
 void main() {
   v_BufPos = a_BufPos;
-  gl_Position = vec4(a_position, 0.0, 0.0) + u_Proj * u_View * u_Model * vec4(a_Pos, 0.0, 1.0);
+  gl_Position = vec4((u_Camera * vec4(a_position, 0.0, 1.0)).xy, 0.0, 0.0) + u_Proj * u_View * u_Model * vec4(a_Pos, 0.0, 1.0);
 }
@@ -8,7 +8,11 @@ uniform b_TextPosition {
   vec2 a_position;
 };
 
+uniform b_TextScale {
+  vec2 a_scale;
+};
+
 void main() {
-  v_BufPos = a_BufPos * vec2(25.0, 50.0);
+  v_BufPos = a_BufPos * a_scale;
   gl_Position = vec4(a_position, 0.0, 0.0) + vec4(a_Pos, 1.0);
 }
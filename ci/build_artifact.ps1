@@ -2,6 +2,8 @@ mkdir hinterland
 mkdir hinterland\assets
 copy assets\*.png hinterland\assets
 copy assets\*.json hinterland\assets
+copy assets\*.ttf hinterland\assets
+copy -Recurse assets\shaders hinterland\assets\shaders
 copy -Recurse assets\maps hinterland\assets\maps
 copy -Recurse assets\audio hinterland\assets\audio
 copy target\release\hinterland.exe hinterland\hinterland.exe
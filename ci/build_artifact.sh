@@ -7,6 +7,8 @@ echo "Building $PACKAGE_NAME"
 mkdir -p hinterland/assets
 cp assets/*.png hinterland/assets
 cp assets/*.json hinterland/assets
+cp assets/*.ttf hinterland/assets
+cp -r assets/shaders hinterland/assets
 cp -r assets/maps hinterland/assets
 cp -r assets/audio hinterland/assets
 cp target/release/hinterland hinterland
@@ -0,0 +1,125 @@
+use std::{collections::HashMap, convert::TryInto, fs, path::Path, sync::Arc};
+
+use crate::game::constants::ASSETS_PACK_EXCLUDE;
+
+const MAGIC: &[u8; 4] = b"HPAK";
+const VERSION: u32 = 1;
+
+// Layout: magic, version, entry count, entries of (name length u16, name, offset u64, size u64), file data.
+// Numbers are little endian and offsets are relative to the start of the file data
+pub struct AssetArchive {
+  data: Arc<[u8]>,
+  entries: HashMap<String, (usize, usize)>,
+}
+
+impl AssetArchive {
+  pub fn parse(data: Arc<[u8]>) -> Result<AssetArchive, String> {
+    let mut reader = Reader { data: &data, pos: 0 };
+    if reader.take(4)? != MAGIC {
+      return Err("Not an asset archive".to_string());
+    }
+    let version = reader.u32()?;
+    if version != VERSION {
+      return Err(format!("Unsupported asset archive version {}", version));
+    }
+
+    let count = reader.u32()?;
+    let mut index = Vec::new();
+    for _ in 0..count {
+      let name_len = reader.u16()? as usize;
+      let name = String::from_utf8(reader.take(name_len)?.to_vec())
+        .map_err(|e| format!("Invalid asset name: {}", e))?;
+      let offset = reader.u64()? as usize;
+      let size = reader.u64()? as usize;
+      index.push((name, offset, size));
+    }
+
+    let start = reader.pos;
+    let mut entries = HashMap::new();
+    for (name, offset, size) in index {
+      let begin = start.checked_add(offset).filter(|b| b.checked_add(size).is_some_and(|end| end <= data.len()))
+        .ok_or_else(|| format!("Asset {} is outside of the archive", name))?;
+      entries.insert(name, (begin, size));
+    }
+
+    Ok(AssetArchive {
+      data,
+      entries,
+    })
+  }
+
+  pub fn read(&self, name: &str) -> Option<Arc<[u8]>> {
+    self.entries.get(name).map(|&(begin, size)| self.data[begin..begin + size].into())
+  }
+
+  pub fn contains(&self, name: &str) -> bool {
+    self.entries.contains_key(name)
+  }
+
+  pub fn pack(root: &Path) -> Result<Vec<u8>, String> {
+    let mut files = Vec::new();
+    collect_files(root, "", &mut files)?;
+    files.sort();
+
+    let mut header = MAGIC.to_vec();
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    let mut data = Vec::new();
+    for name in &files {
+      let bytes = fs::read(root.join(name)).map_err(|e| format!("Asset {} read error: {}", name, e))?;
+      let name_len: u16 = name.len().try_into().map_err(|_| format!("Asset name {} is too long", name))?;
+      header.extend_from_slice(&name_len.to_le_bytes());
+      header.extend_from_slice(name.as_bytes());
+      header.extend_from_slice(&(data.len() as u64).to_le_bytes());
+      header.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+      data.extend_from_slice(&bytes);
+    }
+    header.extend_from_slice(&data);
+    Ok(header)
+  }
+}
+
+fn collect_files(root: &Path, prefix: &str, files: &mut Vec<String>) -> Result<(), String> {
+  let dir = root.join(prefix);
+  let entries = fs::read_dir(&dir).map_err(|e| format!("Assets directory {} read error: {}", dir.display(), e))?;
+  for entry in entries {
+    let entry = entry.map_err(|e| e.to_string())?;
+    let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+    if ASSETS_PACK_EXCLUDE.contains(&name.as_str()) {
+      continue;
+    }
+    if entry.path().is_dir() {
+      collect_files(root, &format!("{}/", name), files)?;
+    } else {
+      files.push(name);
+    }
+  }
+  Ok(())
+}
+
+struct Reader<'a> {
+  data: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Reader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+    let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len())
+      .ok_or_else(|| "Asset archive is truncated".to_string())?;
+    let bytes = &self.data[self.pos..end];
+    self.pos = end;
+    Ok(bytes)
+  }
+
+  fn u16(&mut self) -> Result<u16, String> {
+    Ok(u16::from_le_bytes(self.take(2)?.try_into().expect("Slice should have 2 bytes")))
+  }
+
+  fn u32(&mut self) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("Slice should have 4 bytes")))
+  }
+
+  fn u64(&mut self) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("Slice should have 8 bytes")))
+  }
+}
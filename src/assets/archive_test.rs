@@ -0,0 +1,33 @@
+#[test]
+fn asset_archive_test() {
+  use std::fs;
+
+  use crate::assets::{AssetManager, archive::AssetArchive, asset_locations};
+
+  let root = std::env::temp_dir().join(format!("hinterland_archive_{}", std::process::id()));
+  fs::create_dir_all(root.join("maps")).expect("Assets directory should be created");
+  fs::create_dir_all(root.join("preview-gifs")).expect("Assets directory should be created");
+  fs::write(root.join("waves.json"), "{}").expect("Asset should be written");
+  fs::write(root.join("maps/tilemap.tmx"), "<map/>").expect("Asset should be written");
+  fs::write(root.join("preview-gifs/preview.gif"), "gif").expect("Asset should be written");
+
+  let packed = AssetArchive::pack(&root).expect("Assets should pack");
+  fs::write(root.join("assets.pak"), &packed).expect("Archive should be written");
+
+  let mut assets = AssetManager::open(&root.join("assets.pak")).expect("Archive should open");
+  assert_eq!(assets.text("maps/tilemap.tmx"), "<map/>");
+  assert_eq!(assets.text("waves.json"), "{}");
+  assert!(assets.exists("waves.json"));
+  assert!(!assets.exists("preview-gifs/preview.gif"), "Preview images should not be packed");
+  assert!(assets.try_bytes("zombie.json").is_err());
+  assert!(assets.changed().is_empty(), "Archives should not be watched");
+
+  assert!(AssetArchive::parse(packed[..packed.len() - 1].into()).is_err(), "Truncated archive should fail");
+  assert!(AssetArchive::parse(b"PK\x03\x04"[..].into()).is_err(), "Other formats should fail");
+
+  assert_eq!(asset_locations(Some("game.pak".to_string()), Some(root.clone())), [std::path::PathBuf::from("game.pak")]);
+  assert_eq!(asset_locations(None, Some(root.clone())),
+             [root.join("assets"), root.join("assets.pak"), "assets".into(), "assets.pak".into()]);
+
+  fs::remove_dir_all(&root).expect("Assets directory should be removed");
+}
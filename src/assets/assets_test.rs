@@ -0,0 +1,31 @@
+#[test]
+fn asset_cache_test() {
+  use std::{fs, sync::Arc, time::{Duration, SystemTime}};
+
+  use crate::assets::{AssetManager, reload};
+
+  let root = std::env::temp_dir().join(format!("hinterland_assets_{}", std::process::id()));
+  fs::create_dir_all(root.join("maps")).expect("Assets directory should be created");
+  fs::write(root.join("maps/sheet.json"), "first").expect("Asset should be written");
+
+  let mut assets = AssetManager::new(&root);
+  let first = assets.bytes("maps/sheet.json");
+  assert!(Arc::ptr_eq(&first, &assets.bytes("maps/sheet.json")), "Loaded assets should be cached");
+  assert_eq!(assets.text("maps/sheet.json"), "first");
+  assert!(assets.try_bytes("missing.png").is_err());
+  assert!(assets.poll().is_empty(), "Unchanged assets should not reload");
+
+  fs::write(root.join("maps/sheet.json"), "second").expect("Asset should be written");
+  fs::File::options().write(true).open(root.join("maps/sheet.json"))
+    .and_then(|f| f.set_modified(SystemTime::now() + Duration::from_secs(5)))
+    .expect("Asset modification time should be set");
+  let changed = assets.poll();
+  assert_eq!(changed, ["maps/sheet.json"]);
+  assert_eq!(assets.text("maps/sheet.json"), "second", "Changed assets should be read again");
+
+  assert_eq!(reload(&changed, &["other.png"], || Ok(1)), None, "Unrelated changes should not reload");
+  assert_eq!(reload(&changed, &["maps/sheet.json"], || Ok(1)), Some(1));
+  assert_eq!(reload::<i32, _>(&changed, &["maps/sheet.json"], || Err("broken".to_string())), None, "Failed reloads should keep the old asset");
+
+  fs::remove_dir_all(&root).expect("Assets directory should be removed");
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, env, fs, path::{Path, PathBuf}, sync::Arc, time::{Instant, SystemTime}};
+
+use crate::assets::archive::AssetArchive;
+use crate::game::constants::{ASSET_POLL_INTERVAL, ASSETS_ARCHIVE, ASSETS_ENV, ASSETS_ROOT};
+
+pub mod archive;
+mod archive_test;
+mod assets_test;
+
+enum AssetSource {
+  Directory(PathBuf),
+  Archive(AssetArchive),
+}
+
+// Assets are named by their path relative to the root, loaded files are cached until they change
+pub struct AssetManager {
+  source: AssetSource,
+  cache: HashMap<String, Arc<[u8]>>,
+  modified: HashMap<String, Option<SystemTime>>,
+  last_poll: Instant,
+}
+
+impl AssetManager {
+  pub fn new<P: AsRef<Path>>(root: P) -> AssetManager {
+    AssetManager::with_source(AssetSource::Directory(root.as_ref().to_path_buf()))
+  }
+
+  fn with_source(source: AssetSource) -> AssetManager {
+    AssetManager {
+      source,
+      cache: HashMap::new(),
+      modified: HashMap::new(),
+      last_poll: Instant::now(),
+    }
+  }
+
+  // A directory is read file by file, any other file is opened as a packed archive
+  pub fn open(path: &Path) -> Result<AssetManager, String> {
+    if path.is_dir() {
+      return Ok(AssetManager::new(path));
+    }
+    let data = fs::read(path).map_err(|e| format!("Assets {} not found: {}", path.display(), e))?;
+    AssetArchive::parse(data.into())
+      .map(|archive| AssetManager::with_source(AssetSource::Archive(archive)))
+      .map_err(|e| format!("Assets {} error: {}", path.display(), e))
+  }
+
+  // Uses the given path or the environment variable when set, otherwise looks next to the executable and then in the working directory
+  pub fn locate(path: Option<&str>) -> AssetManager {
+    let explicit = path.map(str::to_string).or_else(|| env::var(ASSETS_ENV).ok());
+    let exe_dir = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let locations = asset_locations(explicit, exe_dir);
+    match locations.iter().find(|location| location.exists()) {
+      Some(location) => match AssetManager::open(location) {
+        Ok(assets) => assets,
+        Err(e) => panic!("{}", e),
+      },
+      None => panic!("Assets not found, tried {}", locations.iter().map(|l| l.display().to_string()).collect::<Vec<String>>().join(", ")),
+    }
+  }
+
+  pub fn exists(&self, name: &str) -> bool {
+    match self.source {
+      AssetSource::Directory(ref root) => root.join(name).is_file(),
+      AssetSource::Archive(ref archive) => archive.contains(name),
+    }
+  }
+
+  pub fn try_bytes(&mut self, name: &str) -> Result<Arc<[u8]>, String> {
+    if let Some(bytes) = self.cache.get(name) {
+      return Ok(Arc::clone(bytes));
+    }
+    let bytes: Arc<[u8]> = match self.source {
+      AssetSource::Directory(ref root) => {
+        let path = root.join(name);
+        let bytes = fs::read(&path).map_err(|e| format!("Asset {} not found: {}", path.display(), e))?;
+        self.modified.insert(name.to_string(), modified_time(&path));
+        bytes.into()
+      }
+      AssetSource::Archive(ref archive) => archive.read(name).ok_or_else(|| format!("Asset {} not found in archive", name))?,
+    };
+    self.cache.insert(name.to_string(), Arc::clone(&bytes));
+    Ok(bytes)
+  }
+
+  pub fn bytes(&mut self, name: &str) -> Arc<[u8]> {
+    match self.try_bytes(name) {
+      Ok(bytes) => bytes,
+      Err(e) => panic!("{}", e),
+    }
+  }
+
+  pub fn try_text(&mut self, name: &str) -> Result<String, String> {
+    let bytes = self.try_bytes(name)?;
+    String::from_utf8(bytes.to_vec()).map_err(|e| format!("Asset {} is not valid UTF-8: {}", name, e))
+  }
+
+  pub fn text(&mut self, name: &str) -> String {
+    match self.try_text(name) {
+      Ok(text) => text,
+      Err(e) => panic!("{}", e),
+    }
+  }
+
+  // Returns the loaded assets changed on disk since the last poll, only debug builds watch asset directories
+  pub fn changed(&mut self) -> Vec<String> {
+    if !cfg!(debug_assertions) || self.last_poll.elapsed().as_secs_f64() < ASSET_POLL_INTERVAL {
+      return Vec::new();
+    }
+    self.last_poll = Instant::now();
+    self.poll()
+  }
+
+  fn poll(&mut self) -> Vec<String> {
+    let root = match self.source {
+      AssetSource::Directory(ref root) => root,
+      AssetSource::Archive(_) => return Vec::new(),
+    };
+    let mut changed = self.modified.iter_mut()
+      .filter_map(|(name, modified)| {
+        let current = modified_time(&root.join(name));
+        if current != *modified {
+          *modified = current;
+          Some(name.clone())
+        } else {
+          None
+        }
+      })
+      .collect::<Vec<String>>();
+    changed.sort();
+    for name in &changed {
+      self.cache.remove(name);
+    }
+    changed
+  }
+}
+
+fn asset_locations(explicit: Option<String>, exe_dir: Option<PathBuf>) -> Vec<PathBuf> {
+  if let Some(path) = explicit {
+    return vec![PathBuf::from(path)];
+  }
+  exe_dir.into_iter()
+    .chain(Some(PathBuf::new()))
+    .flat_map(|dir| vec![dir.join(ASSETS_ROOT), dir.join(ASSETS_ARCHIVE)])
+    .collect()
+}
+
+// Reloads when one of the names changed, failed reloads keep the current asset in use
+pub fn reload<T, L>(changed: &[String], names: &[&str], load: L) -> Option<T>
+  where L: FnOnce() -> Result<T, String> {
+  if !changed.iter().any(|c| names.contains(&c.as_str())) {
+    return None;
+  }
+  match load() {
+    Ok(asset) => Some(asset),
+    Err(e) => {
+      eprintln!("Asset reload failed: {}", e);
+      None
+    }
+  }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+  fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -1,4 +1,4 @@
-use std::{fs::File, io::BufReader};
+use std::{io::Cursor, sync::Arc};
 
 use crossbeam_channel as channel;
 use rodio;
@@ -6,6 +6,7 @@ use rodio::Sink;
 use specs;
 use specs::prelude::ReadStorage;
 
+use crate::assets::AssetManager;
 use crate::character::{CharacterDrawable, controls::CharacterInputState};
 use crate::game::constants::PISTOL_AUDIO_PATH;
 
@@ -18,25 +19,28 @@ pub enum Effects {
 pub struct AudioSystem {
   effects: Effects,
   sink: Sink,
+  pistol: Arc<[u8]>,
   queue: channel::Receiver<Effects>,
 }
 
 impl AudioSystem {
-  pub fn new() -> (AudioSystem, channel::Sender<Effects>) {
+  pub fn new(volume: f32, assets: &mut AssetManager) -> (AudioSystem, channel::Sender<Effects>) {
     #[allow(deprecated)]
       let (tx, rx) = channel::unbounded();
     let endpoint = rodio::default_output_device().unwrap();
+    let sink = Sink::new(&endpoint);
+    sink.set_volume(volume);
 
     (AudioSystem {
       effects: Effects::None,
-      sink: Sink::new(&endpoint),
+      sink,
+      pistol: assets.bytes(PISTOL_AUDIO_PATH),
       queue: rx,
     }, tx)
   }
 
   fn play_effect(&mut self) {
-    let file = File::open(PISTOL_AUDIO_PATH).unwrap();
-    let pistol_data = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let pistol_data = rodio::Decoder::new(Cursor::new(Arc::clone(&self.pistol))).unwrap();
     if self.sink.empty() {
       self.sink.append(pistol_data);
     }
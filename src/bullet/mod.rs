@@ -1,79 +1,61 @@
-use std::f32;
 use std::f32::consts::PI;
 
 use cgmath::Point2;
 use gfx;
 use specs;
-use specs::prelude::{Read, ReadStorage, WriteStorage};
+use specs::prelude::{Read, ReadExpect, WriteStorage};
 
+use crate::assets;
+use crate::assets::AssetManager;
 use crate::bullet::bullets::Bullets;
 use crate::bullet::collision::Collision;
-use crate::character::controls::CharacterInputState;
-use crate::game::constants::{ASPECT_RATIO, BULLET_SPEED, VIEW_DISTANCE};
+use crate::game::constants::BULLET_SPEED;
 use crate::gfx_app::{ColorFormat, DepthFormat};
-use crate::graphics::{camera::CameraInputState, can_move, dimensions::{Dimensions, get_projection, get_view_matrix}};
+use crate::graphics::{can_move, DeltaTime};
 use crate::graphics::can_move_to_tile;
 use crate::graphics::mesh::PlainMesh;
-use crate::shaders::{bullet_pipeline, Position, Projection, Rotation};
+use crate::shaders::{bullet_pipeline, load_pipeline, Position, Projection, RenderViews, Rotation};
+use crate::terrain::collision::CollisionGrid;
 
 pub mod bullets;
 pub mod collision;
 
-const SHADER_VERT: &[u8] = include_bytes!("../shaders/bullet.v.glsl");
-const SHADER_FRAG: &[u8] = include_bytes!("../shaders/bullet.f.glsl");
+const SHADER_VERT: &str = "shaders/bullet.v.glsl";
+const SHADER_FRAG: &str = "shaders/bullet.f.glsl";
 
 const SCALING_FACTOR: f32 = 5.0 / 3.0;
 
 #[derive(PartialEq)]
 pub struct BulletDrawable {
-  projection: Projection,
   pub position: Position,
+  last_position: Position,
   pub rotation: Rotation,
-  previous_position: Position,
-  offset_delta: Position,
   pub movement_direction: Point2<f32>,
   pub status: collision::Collision,
 }
 
 impl BulletDrawable {
   pub fn new(position: Position, movement_direction: Point2<f32>, direction: f32) -> BulletDrawable {
-    let view = get_view_matrix(VIEW_DISTANCE);
-    let projection = get_projection(view, ASPECT_RATIO);
     let rotation = Rotation::new(direction * PI / 180.0);
     BulletDrawable {
-      projection,
       position,
+      last_position: position,
       rotation,
-      previous_position: Position::origin(),
-      offset_delta: Position::origin(),
       movement_direction,
       status: Collision::Flying,
     }
   }
 
-  pub fn update(&mut self, world_to_clip: &Projection, ci: &CharacterInputState) {
-    self.projection = *world_to_clip;
+  pub fn update(&mut self, delta: f32, collision: &CollisionGrid) {
+    self.last_position = self.position;
 
-    self.offset_delta =
-      if (ci.movement.x() - self.previous_position.x()).abs() > f32::EPSILON ||
-        (ci.movement.y() - self.previous_position.y()).abs() > f32::EPSILON {
-        ci.movement - self.previous_position
-      } else {
-        self.offset_delta
-      };
+    let speed = BULLET_SPEED * delta;
+    self.position = self.position -
+      Position::new(self.movement_direction.x * speed / SCALING_FACTOR, -self.movement_direction.y * speed);
 
-    self.previous_position = Position::new(
-      ci.movement.x() - (self.movement_direction.x * BULLET_SPEED / SCALING_FACTOR),
-      ci.movement.y() + (self.movement_direction.y * BULLET_SPEED));
-
-    self.position = self.position + self.offset_delta +
-      Position::new(self.movement_direction.x * BULLET_SPEED / SCALING_FACTOR, -self.movement_direction.y * BULLET_SPEED);
-
-    let tile_pos = ci.movement - self.position;
-
-    self.status = if !can_move(self.position) {
+    self.status = if !can_move(collision.size(), self.position) {
       Collision::OutOfBounds
-    } else if !can_move_to_tile(tile_pos) {
+    } else if !can_move_to_tile(collision, self.position) {
       Collision::Hit
     } else {
       Collision::Flying
@@ -87,6 +69,7 @@ pub struct BulletDrawSystem<R: gfx::Resources> {
 
 impl<R: gfx::Resources> BulletDrawSystem<R> {
   pub fn new<F>(factory: &mut F,
+                assets: &mut AssetManager,
                 rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
                 dsv: gfx::handle::DepthStencilView<R, DepthFormat>) -> BulletDrawSystem<R>
     where F: gfx::Factory<R> {
@@ -94,8 +77,8 @@ impl<R: gfx::Resources> BulletDrawSystem<R> {
 
     let mesh = PlainMesh::new_with_data(factory, Point2::new(2.4, 0.8), None, None, None);
 
-    let pso = factory.create_pipeline_simple(SHADER_VERT, SHADER_FRAG, bullet_pipeline::new())
-      .expect("Bullet shader loading error");
+    let pso = load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, bullet_pipeline::new())
+      .unwrap_or_else(|e| panic!("Bullet shader loading error: {}", e));
 
     let pipeline_data = bullet_pipeline::Data {
       vbuf: mesh.vertex_buffer,
@@ -111,12 +94,27 @@ impl<R: gfx::Resources> BulletDrawSystem<R> {
     }
   }
 
+  pub fn update_views(&mut self,
+                      rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+                      dsv: gfx::handle::DepthStencilView<R, DepthFormat>) {
+    self.bundle.data.set_views(rtv, dsv);
+  }
+
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, changed: &[String])
+    where F: gfx::Factory<R> {
+    if let Some(pso) = assets::reload(changed, &[SHADER_VERT, SHADER_FRAG], || load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, bullet_pipeline::new())) {
+      self.bundle.pso = pso;
+    }
+  }
+
   pub fn draw<C>(&mut self,
                  drawable: &BulletDrawable,
+                 projection: &Projection,
+                 alpha: f32,
                  encoder: &mut gfx::Encoder<R, C>)
     where C: gfx::CommandBuffer<R> {
-    encoder.update_constant_buffer(&self.bundle.data.projection_cb, &drawable.projection);
-    encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.position);
+    encoder.update_constant_buffer(&self.bundle.data.projection_cb, projection);
+    encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.last_position.lerp(drawable.position, alpha));
     encoder.update_constant_buffer(&self.bundle.data.rotation_cb, &drawable.rotation);
     self.bundle.encode(encoder);
   }
@@ -125,19 +123,16 @@ impl<R: gfx::Resources> BulletDrawSystem<R> {
 pub struct PreDrawSystem;
 
 impl<'a> specs::prelude::System<'a> for PreDrawSystem {
-  type SystemData = (ReadStorage<'a, CameraInputState>,
-                     WriteStorage<'a, Bullets>,
-                     ReadStorage<'a, CharacterInputState>,
-                     Read<'a, Dimensions>);
+  type SystemData = (WriteStorage<'a, Bullets>,
+                     ReadExpect<'a, CollisionGrid>,
+                     Read<'a, DeltaTime>);
 
-  fn run(&mut self, (camera_input, mut bullets, character_input, dim): Self::SystemData) {
+  fn run(&mut self, (mut bullets, collision, dt): Self::SystemData) {
     use specs::join::Join;
 
-    for (camera, bs, ci) in (&camera_input, &mut bullets, &character_input).join() {
-      let world_to_clip = dim.world_to_projection(camera);
-
+    for bs in (&mut bullets).join() {
       for b in &mut bs.bullets {
-        b.update(&world_to_clip, ci);
+        b.update(dt.0 as f32, &collision);
       }
     }
   }
@@ -1,11 +1,12 @@
 use crossbeam_channel as channel;
 use specs;
-use specs::prelude::{Read, WriteStorage};
+use specs::prelude::{Read, ReadExpect, WriteStorage};
 
 use crate::character::CharacterDrawable;
 use crate::game::constants::{CHARACTER_X_SPEED, CHARACTER_Y_SPEED};
-use crate::graphics::{camera::CameraInputState, can_move_to_tile, DeltaTime, orientation::{Orientation, Stance}};
+use crate::graphics::{can_move_to_tile, DeltaTime, orientation::{Orientation, Stance}};
 use crate::shaders::Position;
+use crate::terrain::collision::CollisionGrid;
 
 pub struct CharacterInputState {
   pub movement: Position,
@@ -24,15 +25,17 @@ impl CharacterInputState {
     }
   }
 
-  pub fn update(&mut self, camera: &mut CameraInputState, css: &CharacterControlSystem) {
-    if css.y_move.is_none() && css.x_move.is_none() {
+  pub fn update(&mut self, css: &CharacterControlSystem, collision: &CollisionGrid, delta: f32) {
+    let x_move = css.x_move.map(|x| x * delta);
+    let y_move = css.y_move.map(|y| y * delta);
+
+    if y_move.is_none() && x_move.is_none() {
       self.orientation = Orientation::Normal;
-    } else if css.x_move.is_none() {                  // Horizontal/vertical movement
-      if let Some(y) = css.y_move {
+    } else if x_move.is_none() {                  // Horizontal/vertical movement
+      if let Some(y) = y_move {
         let vertical_movement = self.movement + Position::new(0.0, y);
-        if !self.is_colliding || can_move_to_tile(vertical_movement) {
+        if !self.is_colliding || can_move_to_tile(collision, vertical_movement) {
           self.movement = vertical_movement;
-          camera.movement = camera.movement - Position::new(0.0, y);
           self.orientation = match y {
             y if y < 0.0 => Orientation::Up,
             y if y > 0.0 => Orientation::Down,
@@ -40,14 +43,13 @@ impl CharacterInputState {
           };
         }
       }
-    } else if let Some(x) = css.x_move {        // Diagonal movement
+    } else if let Some(x) = x_move {        // Diagonal movement
       let horizontal_move = self.movement + Position::new(x, 0.0);
-      if let Some(y) = css.y_move {
+      if let Some(y) = y_move {
         let horizontal_movement = Position::new(x / 1.5, 0.0);
         let vertical_movement = Position::new(0.0, y / 1.666);
-        if !self.is_colliding || can_move_to_tile(self.movement + horizontal_movement + vertical_movement) {
+        if !self.is_colliding || can_move_to_tile(collision, self.movement + horizontal_movement + vertical_movement) {
           self.movement = self.movement + horizontal_movement + vertical_movement;
-          camera.movement = camera.movement + horizontal_movement - vertical_movement;
 
           self.orientation = match (x, y) {
             (x, y) if x > 0.0 && y > 0.0 => Orientation::DownLeft,
@@ -57,10 +59,9 @@ impl CharacterInputState {
             _ => Orientation::Normal,
           };
         }
-      } else if css.y_move.is_none() && !self.is_colliding || can_move_to_tile(horizontal_move) {
+      } else if y_move.is_none() && !self.is_colliding || can_move_to_tile(collision, horizontal_move) {
         let horizontal_movement = Position::new(x, 0.0);
         self.movement = self.movement + horizontal_movement;
-        camera.movement = camera.movement + horizontal_movement;
         self.orientation = match x {
           x if x < 0.0 => Orientation::Right,
           x if x > 0.0 => Orientation::Left,
@@ -82,6 +83,7 @@ impl specs::prelude::Component for CharacterInputState {
   type Storage = specs::storage::VecStorage<CharacterInputState>;
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CharacterControl {
   Left,
   Right,
@@ -121,10 +123,10 @@ impl CharacterControlSystem {
 impl<'a> specs::prelude::System<'a> for CharacterControlSystem {
   type SystemData = (WriteStorage<'a, CharacterInputState>,
                      WriteStorage<'a, CharacterDrawable>,
-                     WriteStorage<'a, CameraInputState>,
+                     ReadExpect<'a, CollisionGrid>,
                      Read<'a, DeltaTime>);
 
-  fn run(&mut self, (mut character_input, mut character, mut camera_input, d): Self::SystemData) {
+  fn run(&mut self, (mut character_input, mut character, collision, d): Self::SystemData) {
     use specs::join::Join;
 
     let delta = d.0;
@@ -148,9 +150,9 @@ impl<'a> specs::prelude::System<'a> for CharacterControlSystem {
         }
       }
 
-      for (ci, c, camera) in (&mut character_input, &mut character, &mut camera_input).join() {
+      for (ci, c) in (&mut character_input, &mut character).join() {
         if c.stance != Stance::NormalDeath {
-          ci.update(camera, self);
+          ci.update(self, &collision, delta as f32);
         }
         if self.is_reloading && c.stats.magazines > 0 && c.stats.ammunition < 10 {
           c.stats.ammunition = 10;
@@ -1,34 +1,35 @@
-use std;
-
 use cgmath::Point2;
 use gfx;
 use specs;
 use specs::prelude::{Read, ReadStorage, WriteStorage};
 
 use crate::character::{character_stats::CharacterStats, controls::CharacterInputState};
-use crate::critter::{CharacterSprite, CritterData};
-use crate::data;
-use crate::game::constants::{AMMO_POSITIONS, ASPECT_RATIO, CHARACTER_SHEET_TOTAL_WIDTH, RUN_SPRITE_OFFSET, SPRITE_OFFSET, VIEW_DISTANCE, SMALL_HILLS};
+use crate::assets;
+use crate::assets::AssetManager;
+use crate::critter::CritterSprites;
+use crate::game::console::DevSettings;
+use crate::game::constants::CHARACTER_JSON_PATH;
 use crate::gfx_app::{ColorFormat, DepthFormat};
 use crate::gfx_app::mouse_controls::MouseInputState;
-use crate::graphics::{camera::CameraInputState, dimensions::{Dimensions, get_projection, get_view_matrix}, get_orientation_from_center, orientation::{Orientation, Stance}, overlaps, texture::load_texture, check_terrain_elevation};
+use crate::graphics::{camera::CameraInputState, dimensions::Dimensions, get_orientation_from_center, orientation::{Orientation, Stance}, spatial_grid::{SpatialGrid, SpatialIndex}, check_terrain_elevation};
+use crate::graphics::animation::{AnimationSet, Animator};
+use crate::graphics::atlas::AtlasFrame;
 use crate::graphics::mesh::{RectangularTexturedMesh, Geometry};
 use crate::graphics::texture::Texture;
-use crate::shaders::{CharacterSheet, critter_pipeline, Position, Projection};
-use crate::terrain_object::{terrain_objects::TerrainObjects, TerrainObjectDrawable, TerrainTexture};
-use crate::zombie::{ZombieDrawable, zombies::Zombies};
+use crate::shaders::{critter_pipeline, load_pipeline, Position, Projection, RenderViews};
+use crate::terrain_object::{terrain_objects::TerrainObjects, TerrainObjectDrawable};
 
 pub mod controls;
 mod character_stats;
 
-const SHADER_VERT: &[u8] = include_bytes!("../shaders/character.v.glsl");
-const SHADER_FRAG: &[u8] = include_bytes!("../shaders/character.f.glsl");
+const SHADER_VERT: &str = "shaders/character.v.glsl";
+const SHADER_FRAG: &str = "shaders/character.f.glsl";
 
 #[derive(Clone)]
 pub struct CharacterDrawable {
   pub stats: CharacterStats,
-  projection: Projection,
   pub position: Position,
+  last_position: Position,
   orientation: Orientation,
   pub stance: Stance,
   direction: Orientation,
@@ -36,45 +37,32 @@ pub struct CharacterDrawable {
 
 impl CharacterDrawable {
   pub fn new() -> CharacterDrawable {
-    let view = get_view_matrix(VIEW_DISTANCE);
-    let projection = get_projection(view, ASPECT_RATIO);
     let stats = CharacterStats::new();
     CharacterDrawable {
       stats,
-      projection,
       position: Position::origin(),
+      last_position: Position::origin(),
       orientation: Orientation::Right,
       stance: Stance::Walking,
       direction: Orientation::Right,
     }
   }
 
-  pub fn update(&mut self, world_to_clip: &Projection, ci: &CharacterInputState, mouse_input: &MouseInputState,
-                dimensions: &Dimensions, objs: &mut Vec<TerrainObjectDrawable>, zombies: &[ZombieDrawable]) {
-    self.projection = *world_to_clip;
-
-    self.position.position[1] = check_terrain_elevation(ci.movement - self.position, &SMALL_HILLS);
+  pub fn update(&mut self, ci: &CharacterInputState, mouse_input: &MouseInputState,
+                dimensions: &Dimensions, objs: &mut Vec<TerrainObjectDrawable>, index: &SpatialIndex, god_mode: bool) {
+    self.last_position = self.position;
 
-    fn zombie_not_dead(z: &ZombieDrawable) -> bool {
-      z.stance != Stance::NormalDeath &&
-        z.stance != Stance::CriticalDeath
+    if self.is_dead() {
+      return;
     }
 
-    for idx in 0..AMMO_POSITIONS.len() {
-      self.ammo_pick_up(ci.movement, objs, idx);
-    }
+    self.position = ci.movement - Position::new(0.0, check_terrain_elevation(ci.movement, &index.hills));
 
-    if !cfg!(feature = "godmode") &&
-      zombies.iter()
-        .any(|z|
-          zombie_not_dead(z) &&
-            overlaps(ci.movement,
-                     ci.movement - z.position,
-                     15.0,
-                     30.0)) {
+    self.ammo_pick_up(ci.movement, objs, &index.pickups);
+
+    if !god_mode && index.zombies.query_aabb(ci.movement, 15.0, 30.0).next().is_some() {
       self.stance = Stance::NormalDeath;
-      println!("Player died");
-      std::process::exit(0);
+      return;
     }
 
     if ci.is_shooting && mouse_input.left_click_point.is_some() && !ci.is_colliding {
@@ -88,9 +76,25 @@ impl CharacterDrawable {
     }
   }
 
-  fn ammo_pick_up(&mut self, movement: Position, objs: &mut Vec<TerrainObjectDrawable>, idx: usize) {
-    if objs.len() > idx && objs[idx].object_type == TerrainTexture::Ammo && overlaps(movement, movement - objs[idx].position, 20.0, 20.0) {
-      self.stats.magazines = 2;
+  pub fn is_dead(&self) -> bool {
+    self.stance == Stance::NormalDeath
+  }
+
+  fn clip(&self) -> &'static str {
+    match self.stance {
+      Stance::Walking => "run",
+      Stance::Firing => "fire",
+      _ => "still",
+    }
+  }
+
+  fn ammo_pick_up(&mut self, movement: Position, objs: &mut Vec<TerrainObjectDrawable>, pickups: &SpatialGrid<usize>) {
+    let mut picked = pickups.query_aabb(movement, 20.0, 20.0)
+      .map(|(_, idx)| *idx)
+      .collect::<Vec<usize>>();
+    picked.sort_unstable_by(|a, b| b.cmp(a));
+    for idx in picked {
+      self.stats.magazines = objs[idx].magazines;
       objs.remove(idx);
     }
   }
@@ -108,24 +112,26 @@ impl specs::prelude::Component for CharacterDrawable {
 
 pub struct CharacterDrawSystem<R: gfx::Resources> {
   bundle: gfx::pso::bundle::Bundle<R, critter_pipeline::Data<R>>,
-  data: Vec<CritterData>,
+  sprites: CritterSprites<R>,
+  size: [f32; 2],
 }
 
 impl<R: gfx::Resources> CharacterDrawSystem<R> {
   pub fn new<F>(factory: &mut F,
+                assets: &mut AssetManager,
                 rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
                 dsv: gfx::handle::DepthStencilView<R, DepthFormat>) -> CharacterDrawSystem<R>
     where F: gfx::Factory<R> {
     use gfx::traits::FactoryExt;
 
-    let charter_bytes = &include_bytes!("../../assets/character.png")[..];
-    let char_texture = load_texture(factory, charter_bytes);
+    let sprites = CritterSprites::new(factory, assets, CHARACTER_JSON_PATH);
+    let size = Point2::new(20.0, 28.0);
 
     let rect_mesh =
-      RectangularTexturedMesh::new(factory, Texture::new(char_texture, None), Geometry::Rectangle, Point2::new(20.0, 28.0), None, None, None);
+      RectangularTexturedMesh::new(factory, Texture::new(sprites.pages[0].clone(), None), Geometry::Rectangle, size, None, None, None);
 
-    let pso = factory.create_pipeline_simple(SHADER_VERT, SHADER_FRAG, critter_pipeline::new())
-      .expect("Character shader loading error");
+    let pso = load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, critter_pipeline::new())
+      .unwrap_or_else(|e| panic!("Character shader loading error: {}", e));
 
     let pipeline_data = critter_pipeline::Data {
       vbuf: rect_mesh.mesh.vertex_buffer,
@@ -137,45 +143,51 @@ impl<R: gfx::Resources> CharacterDrawSystem<R> {
       out_depth: dsv,
     };
 
-    let data = data::load_character();
-
     CharacterDrawSystem {
       bundle: gfx::Bundle::new(rect_mesh.mesh.slice, pso, pipeline_data),
-      data,
+      sprites,
+      size: [size.x, size.y],
     }
   }
 
-  fn get_next_sprite(&self, character_idx: usize, character_fire_idx: usize, drawable: &mut CharacterDrawable) -> CharacterSheet {
-    let sprite_idx =
-      if drawable.orientation == Orientation::Normal && drawable.stance == Stance::Walking {
-        (drawable.direction as usize * 28 + RUN_SPRITE_OFFSET)
-      } else if drawable.stance == Stance::Walking {
-        drawable.direction = drawable.orientation;
-        (drawable.orientation as usize * 28 + character_idx + RUN_SPRITE_OFFSET)
+  fn get_next_sprite(&self, animator: &Animator, drawable: &mut CharacterDrawable) -> &AtlasFrame {
+    let frame =
+      if drawable.orientation == Orientation::Normal {
+        0
       } else {
-        (drawable.orientation as usize * 8 + character_fire_idx)
-      } as usize;
-
-    let elements_x = CHARACTER_SHEET_TOTAL_WIDTH / (self.data[sprite_idx].data[2] + SPRITE_OFFSET);
-    CharacterSheet {
-      x_div: elements_x,
-      y_div: 0.0,
-      row_idx: 0,
-      index: sprite_idx as f32,
+        drawable.direction = drawable.orientation;
+        animator.frame()
+      };
+    self.sprites.sheet.sprite(animator.sprites(), drawable.direction, frame)
+  }
+
+  pub fn update_views(&mut self,
+                      rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+                      dsv: gfx::handle::DepthStencilView<R, DepthFormat>) {
+    self.bundle.data.set_views(rtv, dsv);
+  }
+
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, animations: &AnimationSet, changed: &[String])
+    where F: gfx::Factory<R> {
+    if let Some(pso) = assets::reload(changed, &[SHADER_VERT, SHADER_FRAG], || load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, critter_pipeline::new())) {
+      self.bundle.pso = pso;
     }
+    self.sprites.reload(factory, assets, animations, changed);
   }
 
   pub fn draw<C>(&mut self,
                  mut drawable: &mut CharacterDrawable,
-                 character: &CharacterSprite,
+                 projection: &Projection,
+                 animator: &Animator,
+                 alpha: f32,
                  encoder: &mut gfx::Encoder<R, C>)
     where C: gfx::CommandBuffer<R> {
-    encoder.update_constant_buffer(&self.bundle.data.projection_cb, &drawable.projection);
-    encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.position);
-    encoder.update_constant_buffer(&self.bundle.data.character_sprite_cb,
-                                   &self.get_next_sprite(character.character_idx,
-                                                         character.character_fire_idx,
-                                                         &mut drawable));
+    encoder.update_constant_buffer(&self.bundle.data.projection_cb, projection);
+    encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.last_position.lerp(drawable.position, alpha));
+    let frame = self.get_next_sprite(animator, &mut drawable);
+    let sprite = frame.sheet(self.size);
+    self.bundle.data.charactersheet.0 = self.sprites.pages[frame.page].clone();
+    encoder.update_constant_buffer(&self.bundle.data.character_sprite_cb, &sprite);
     self.bundle.encode(encoder);
   }
 }
@@ -184,20 +196,23 @@ pub struct PreDrawSystem;
 
 impl<'a> specs::prelude::System<'a> for PreDrawSystem {
   type SystemData = (WriteStorage<'a, CharacterDrawable>,
-                     ReadStorage<'a, CameraInputState>,
+                     WriteStorage<'a, Animator>,
+                     WriteStorage<'a, CameraInputState>,
                      ReadStorage<'a, CharacterInputState>,
                      ReadStorage<'a, MouseInputState>,
                      WriteStorage<'a, TerrainObjects>,
-                     ReadStorage<'a, Zombies>,
-                     Read<'a, Dimensions>);
+                     Read<'a, SpatialIndex>,
+                     Read<'a, Dimensions>,
+                     Read<'a, DevSettings>);
 
-  fn run(&mut self, (mut character, camera_input, character_input, mouse_input, mut terrain_objects, zombies, dim): Self::SystemData) {
+  fn run(&mut self, (mut character, mut animators, mut camera_input, character_input, mouse_input, mut terrain_objects, index, dim, dev): Self::SystemData) {
     use specs::join::Join;
 
-    for (c, camera, ci, mi, to, zs) in
-        (&mut character, &camera_input, &character_input, &mouse_input, &mut terrain_objects, &zombies).join() {
-      let world_to_clip = dim.world_to_projection(camera);
-      c.update(&world_to_clip, ci, mi, &dim, &mut to.objects, &zs.zombies);
+    for (c, animator, camera, ci, mi, to) in
+        (&mut character, &mut animators, &mut camera_input, &character_input, &mouse_input, &mut terrain_objects).join() {
+      camera.follow(ci.movement);
+      c.update(ci, mi, &dim, &mut to.objects, &index, dev.god_mode);
+      animator.play(c.clip());
     }
   }
 }
@@ -1,46 +1,99 @@
-use specs;
+use std::path::Path;
+use std::sync::Arc;
 
-pub struct CharacterSprite {
-  pub character_idx: usize,
-  pub character_fire_idx: usize,
-}
+use gfx;
 
-impl CharacterSprite {
-  pub fn new() -> CharacterSprite {
-    CharacterSprite {
-      character_idx: 0,
-      character_fire_idx: 0,
-    }
-  }
+use crate::assets;
+use crate::assets::AssetManager;
+use crate::critter::sprite_sheet::SpriteSheet;
+use crate::data;
+use crate::game::constants::{CHARACTER_ANIMATIONS_PATH, CHARACTER_JSON_PATH, ZOMBIE_ANIMATIONS_PATH, ZOMBIE_JSON_PATH};
+use crate::graphics::animation::AnimationSet;
+use crate::graphics::texture::load_atlas_textures;
+
+pub mod sprite_sheet;
+mod sprite_sheet_test;
+
+pub struct CritterAnimations {
+  pub character: Arc<AnimationSet>,
+  pub zombie: Arc<AnimationSet>,
+}
 
-  pub fn update_run(&mut self) {
-    if self.character_idx < 12 {
-      self.character_idx += 1;
-    } else {
-      self.character_idx = 0;
+impl CritterAnimations {
+  pub fn new(assets: &mut AssetManager) -> CritterAnimations {
+    CritterAnimations {
+      character: Arc::new(data::load_animations(assets, CHARACTER_JSON_PATH, CHARACTER_ANIMATIONS_PATH)),
+      zombie: Arc::new(data::load_animations(assets, ZOMBIE_JSON_PATH, ZOMBIE_ANIMATIONS_PATH)),
     }
-    self.character_fire_idx = 0;
   }
 
-  pub fn update_fire(&mut self) {
-    if self.character_fire_idx < 3 {
-      self.character_fire_idx += 1;
-    } else {
-      self.character_fire_idx = 0;
+  // Returns the replaced clip sets with their reloads, a reload has to keep every clip the game plays
+  pub fn reload(&mut self, assets: &mut AssetManager, changed: &[String]) -> Vec<(Arc<AnimationSet>, Arc<AnimationSet>)> {
+    let mut reloaded = Vec::new();
+    let sets = vec![
+      (&mut self.character, CHARACTER_JSON_PATH, CHARACTER_ANIMATIONS_PATH),
+      (&mut self.zombie, ZOMBIE_JSON_PATH, ZOMBIE_ANIMATIONS_PATH),
+    ];
+    for (animations, sheet_path, path) in sets {
+      let current = Arc::clone(animations);
+      let load = || {
+        let loaded = data::try_load_animations(assets, sheet_path, path)?;
+        match current.clips().find(|(name, _)| !loaded.contains(name)) {
+          Some((name, _)) => Err(format!("Animations {} are missing clip {}", path, name)),
+          None => Ok(loaded),
+        }
+      };
+      if let Some(loaded) = assets::reload(changed, &[sheet_path, path], load) {
+        *animations = Arc::new(loaded);
+        reloaded.push((current, Arc::clone(animations)));
+      }
     }
+    reloaded
   }
 }
 
-impl specs::prelude::Component for CharacterSprite {
-  type Storage = specs::storage::VecStorage<CharacterSprite>;
+// Sprite sheet and its page textures, reloaded together when the JSON or a page image changes
+pub struct CritterSprites<R: gfx::Resources> {
+  pub sheet: SpriteSheet,
+  pub pages: Vec<gfx::handle::ShaderResourceView<R, [f32; 4]>>,
+  path: &'static str,
+  files: Vec<String>,
 }
 
-pub struct CritterData {
-  pub data: [f32; 4]
-}
+impl<R: gfx::Resources> CritterSprites<R> {
+  pub fn new<F>(factory: &mut F, assets: &mut AssetManager, path: &'static str) -> CritterSprites<R>
+    where F: gfx::Factory<R> {
+    match CritterSprites::load(factory, assets, path) {
+      Ok(sprites) => sprites,
+      Err(e) => panic!("Sprite sheet {} error: {}", path, e),
+    }
+  }
 
-impl CritterData {
-  pub fn new(data: [f32; 4]) -> CritterData {
-    CritterData { data }
+  fn load<F>(factory: &mut F, assets: &mut AssetManager, path: &'static str) -> Result<CritterSprites<R>, String>
+    where F: gfx::Factory<R> {
+    let sheet = SpriteSheet::parse(&assets.try_text(path)?)?;
+    let pages = load_atlas_textures(factory, assets, &sheet.atlas, path)?;
+    let dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let files = Some(path.to_string()).into_iter()
+      .chain(sheet.atlas.pages.iter().map(|page| dir.join(&page.image).to_string_lossy().into_owned()))
+      .collect();
+    Ok(CritterSprites {
+      sheet,
+      pages,
+      path,
+      files,
+    })
+  }
+
+  // Sheets missing sprites of a clip are rejected, drawing that clip would fail
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, animations: &AnimationSet, changed: &[String])
+    where F: gfx::Factory<R> {
+    let path = self.path;
+    let files = self.files.iter().map(String::as_str).collect::<Vec<&str>>();
+    let load = || CritterSprites::load(factory, assets, path)
+      .and_then(|sprites| sprites.sheet.check_animations(animations).map(|_| sprites));
+    if let Some(sprites) = assets::reload(changed, &files, load) {
+      *self = sprites;
+    }
   }
 }
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use json;
+
+use crate::data::read_non_negative;
+use crate::graphics::animation::{AnimationClip, AnimationSet};
+use crate::graphics::atlas::{Atlas, AtlasFrame};
+use crate::graphics::orientation::Orientation;
+
+// Atlas frames are named {clip}_{direction}_{frame}, directions go counter-clockwise from right
+pub struct SpriteSheet {
+  pub atlas: Atlas,
+  clips: HashMap<String, Vec<Vec<AtlasFrame>>>,
+}
+
+impl SpriteSheet {
+  pub fn parse(content: &str) -> Result<SpriteSheet, String> {
+    let atlas = Atlas::parse(content)?;
+
+    let mut slots: HashMap<String, Vec<Vec<Option<AtlasFrame>>>> = HashMap::new();
+    for key in atlas.frame_names() {
+      let (name, direction, idx) = parse_frame_key(key)?;
+      let directions = slots.entry(name.to_string()).or_default();
+      if directions.len() <= direction {
+        directions.resize_with(direction + 1, Vec::new);
+      }
+      let frames = &mut directions[direction];
+      if frames.len() <= idx {
+        frames.resize(idx + 1, None);
+      }
+      frames[idx] = atlas.frame(key).cloned();
+    }
+
+    let mut clips = HashMap::new();
+    for (name, directions) in slots {
+      let frame_count = directions.iter().map(Vec::len).max().unwrap_or(0);
+      let mut clip = Vec::with_capacity(directions.len());
+      for (direction, frames) in directions.into_iter().enumerate() {
+        if frames.len() != frame_count || frames.iter().any(Option::is_none) {
+          return Err(format!("Clip {} direction {} is missing frames", name, direction));
+        }
+        clip.push(frames.into_iter().flatten().collect::<Vec<AtlasFrame>>());
+      }
+      clips.insert(name, clip);
+    }
+
+    Ok(SpriteSheet {
+      atlas,
+      clips,
+    })
+  }
+
+  pub fn frame_count(&self, clip: &str) -> Option<usize> {
+    self.clips.get(clip).map(|directions| directions[0].len())
+  }
+
+  pub fn sprite(&self, clip: &str, orientation: Orientation, frame: usize) -> &AtlasFrame {
+    let directions = match self.clips.get(clip) {
+      Some(d) => d,
+      None => panic!("Sprite clip {} not found", clip),
+    };
+    let direction = (orientation as usize * directions.len() / 8).min(directions.len() - 1);
+    let frames = &directions[direction];
+    &frames[frame.min(frames.len() - 1)]
+  }
+
+  pub fn check_animations(&self, animations: &AnimationSet) -> Result<(), String> {
+    match animations.clips().find(|(_, clip)| self.frame_count(&clip.sprites).is_none()) {
+      Some((name, clip)) => Err(format!("Clip {} uses unknown sprites {}", name, clip.sprites)),
+      None => Ok(()),
+    }
+  }
+
+  pub fn animations(&self, content: &str) -> Result<AnimationSet, String> {
+    let data = json::parse(content).map_err(|e| e.to_string())?;
+    if !data.is_object() {
+      return Err("Animations should be an object of clips".to_string());
+    }
+
+    let mut animations = AnimationSet::new();
+    for (name, clip) in data.entries() {
+      let sprites = clip["frames"].as_str().unwrap_or(name);
+      let frames = self.frame_count(sprites)
+        .ok_or_else(|| format!("Clip {} uses unknown sprites {}", name, sprites))?;
+      let mut animation = AnimationClip::new(
+        sprites,
+        frames,
+        read_non_negative(clip, "fps", name)?,
+        clip["loop"].as_bool().ok_or_else(|| format!("Invalid loop value for {}: {}", name, clip["loop"]))?,
+      );
+      for (frame, event) in clip["events"].entries() {
+        let frame = frame.parse::<usize>().ok()
+          .filter(|frame| *frame < frames)
+          .ok_or_else(|| format!("Invalid event frame for {}: {}", name, frame))?;
+        let event = event.as_str().ok_or_else(|| format!("Invalid event for {}: {}", name, event))?;
+        animation = animation.with_event(frame, event);
+      }
+      animations = animations.with_clip(name, animation);
+    }
+    Ok(animations)
+  }
+}
+
+fn parse_frame_key(key: &str) -> Result<(&str, usize, usize), String> {
+  let mut parts = key.rsplitn(3, '_');
+  match (parts.next().map(str::parse::<usize>), parts.next().map(str::parse::<usize>), parts.next()) {
+    (Some(Ok(frame)), Some(Ok(direction)), Some(name)) => Ok((name, direction, frame)),
+    _ => Err(format!("Sprite {} should be named clip_direction_frame", key)),
+  }
+}
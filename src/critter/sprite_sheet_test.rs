@@ -0,0 +1,61 @@
+#[test]
+fn sprite_sheet_parse_test() {
+  use crate::critter::sprite_sheet::SpriteSheet;
+  use crate::graphics::animation::{AnimationClip, AnimationSet};
+  use crate::graphics::orientation::Orientation;
+
+  let sheet = SpriteSheet::parse(r#"{
+    "frames": {
+      "walk_0_0": { "frame": { "x": 0, "y": 12, "w": 10, "h": 10 } },
+      "walk_0_1": { "frame": { "x": 12, "y": 12, "w": 10, "h": 10 } },
+      "walk_1_0": { "frame": { "x": 24, "y": 12, "w": 10, "h": 10 } },
+      "walk_1_1": { "frame": { "x": 36, "y": 12, "w": 10, "h": 10 } }
+    },
+    "meta": { "image": "walk.png", "size": { "w": 48, "h": 24 } }
+  }"#).expect("Sprite sheet should parse");
+
+  assert_eq!(sheet.frame_count("walk"), Some(2));
+  assert_eq!(sheet.frame_count("run"), None);
+
+  let sprite = sheet.sprite("walk", Orientation::Left, 1);
+  assert_eq!(sprite.uv, [0.75, 0.5, 10.0 / 48.0, 10.0 / 24.0], "Left should map to the second of two directions");
+  assert_eq!(sheet.sprite("walk", Orientation::Right, 5).uv[0], 0.25, "Frames past the end should hold the last frame");
+
+  let animations = sheet.animations(r#"{
+    "walk": { "fps": 10, "loop": true, "events": { "1": "step" } },
+    "run": { "frames": "walk", "fps": 20, "loop": true }
+  }"#).expect("Animations should parse");
+  assert_eq!(animations.clip("run").sprites, "walk");
+  assert_eq!(animations.clip("run").frames, 2);
+  assert_eq!(animations.clip("walk").events, [(1, "step".to_string())]);
+  assert!(sheet.check_animations(&animations).is_ok());
+  let unknown = animations.with_clip("fire", AnimationClip::new("fire", 2, 10.0, true));
+  assert!(sheet.check_animations(&unknown).is_err(), "Clips without sprites in the sheet should fail");
+  assert!(sheet.check_animations(&AnimationSet::new()).is_ok());
+
+  assert!(sheet.animations(r#"{ "run": { "fps": 20, "loop": true } }"#).is_err(), "Unknown sprites should fail");
+  assert!(sheet.animations(r#"{ "walk": { "fps": 10, "loop": true, "events": { "2": "step" } } }"#).is_err(),
+          "Events past the last frame should fail");
+  assert!(SpriteSheet::parse(r#"{ "frames": { "walk_0_1": { "frame": { "x": 0, "y": 0, "w": 1, "h": 1 } } },
+    "meta": { "image": "walk.png", "size": { "w": 1, "h": 1 } } }"#).is_err(), "Missing frames should fail");
+  assert!(SpriteSheet::parse(r#"{ "frames": { "walk": { "frame": { "x": 0, "y": 0, "w": 1, "h": 1 } } },
+    "meta": { "image": "walk.png", "size": { "w": 1, "h": 1 } } }"#).is_err(), "Unnumbered frames should fail");
+}
+
+#[test]
+fn critter_animations_test() {
+  use crate::assets::AssetManager;
+  use crate::critter::CritterAnimations;
+  use crate::data;
+  use crate::game::constants::ZOMBIE_JSON_PATH;
+  use crate::graphics::orientation::Orientation;
+
+  let mut assets = AssetManager::locate(None);
+  let animations = CritterAnimations::new(&mut assets);
+  assert_eq!(animations.character.clip("still").sprites, "fire");
+  assert_eq!(animations.zombie.clip("walk").frames, 8);
+
+  let sheet = data::load_sprite_sheet(&mut assets, ZOMBIE_JSON_PATH);
+  let sprite = sheet.sprite("walk", Orientation::Right, 0);
+  assert_eq!(sprite.uv[1], 83.0 / 164.0, "Zombie walk frames should be on the second row");
+}
@@ -1,20 +1,15 @@
-use std::{fs::File, io::BufReader, io::prelude::*, path::Path, string::String, vec::Vec};
+use std::io::Cursor;
 
-use json;
 use json::JsonValue;
 use tiled;
 use tiled::Map;
 
-use crate::critter::CritterData;
-use crate::game::constants::{CHARACTER_BUF_LENGTH, CHARACTER_JSON_PATH, ZOMBIE_JSON_PATH};
+use crate::assets::AssetManager;
+use crate::critter::sprite_sheet::SpriteSheet;
+use crate::graphics::animation::AnimationSet;
 
-pub fn load_map_file(filename: &str) -> Map {
-  let file = match File::open(&Path::new(&filename)) {
-    Ok(f) => f,
-    Err(e) => panic!("File {} not found: {}", filename, e),
-  };
-  let reader = BufReader::new(file);
-  match tiled::parse(reader) {
+pub fn load_map_file(assets: &mut AssetManager, filename: &str) -> Map {
+  match tiled::parse(Cursor::new(assets.bytes(filename))) {
     Ok(m) => m,
     Err(e) => panic!("Map parse error {:?}", e)
   }
@@ -35,85 +30,36 @@ pub fn get_map_tile(map: &Map, layer_index: usize, x: usize, y: usize) -> u32 {
   }
 }
 
-fn read_sprite_file(filename: &str) -> String {
-  let path = Path::new(&filename);
-  let mut file = match File::open(&path) {
-    Ok(f) => f,
-    Err(e) => panic!("File {} not found: {}", filename, e),
-  };
-  let mut buf = String::new();
-  match file.read_to_string(&mut buf) {
-    Ok(_) => buf,
-    Err(e) => panic!("read file {} error {}", filename, e),
+pub fn load_sprite_sheet(assets: &mut AssetManager, filename: &str) -> SpriteSheet {
+  match SpriteSheet::parse(&assets.text(filename)) {
+    Ok(sheet) => sheet,
+    Err(e) => panic!("Sprite sheet {} error: {}", filename, e),
   }
 }
 
-fn get_frame_data(character: &JsonValue, key: &str) -> CritterData {
-  CritterData::new([
-    character["frames"][key]["frame"]["x"].as_f32().unwrap(),
-    character["frames"][key]["frame"]["y"].as_f32().unwrap(),
-    character["frames"][key]["frame"]["w"].as_f32().unwrap(),
-    character["frames"][key]["frame"]["h"].as_f32().unwrap(),
-  ])
-}
-
-pub fn load_character() -> Vec<CritterData> {
-  let mut sprites = Vec::with_capacity(CHARACTER_BUF_LENGTH + 64);
-  let character_json = read_sprite_file(CHARACTER_JSON_PATH);
-  let character = match json::parse(&character_json) {
-    Ok(res) => res,
-    Err(e) => panic!("Character {} parse error {:?}", CHARACTER_JSON_PATH, e),
-  };
-
-  for x in 0..16 {
-    for y in 0..14 {
-      let key = &format!("run_{}_{}", x, y);
-      sprites.push(get_frame_data(&character, key));
-    }
-  }
-
-  for x in 0..15 {
-    for y in 0..4 {
-      let key = &format!("fire_{}_{}", x, y);
-      sprites.push(get_frame_data(&character, key));
-    }
+pub fn load_animations(assets: &mut AssetManager, sheet_filename: &str, filename: &str) -> AnimationSet {
+  match load_sprite_sheet(assets, sheet_filename).animations(&assets.text(filename)) {
+    Ok(animations) => animations,
+    Err(e) => panic!("Animations {} error: {}", filename, e),
   }
-
-  sprites
 }
 
-pub fn load_zombie() -> Vec<CritterData> {
-  let mut sprites = Vec::with_capacity(256);
-  let zombie_json = read_sprite_file(ZOMBIE_JSON_PATH);
-  let zombie = match json::parse(&zombie_json) {
-    Ok(res) => res,
-    Err(e) => panic!("Zombie {} parse error {:?}", ZOMBIE_JSON_PATH, e),
-  };
+pub fn try_load_animations(assets: &mut AssetManager, sheet_filename: &str, filename: &str) -> Result<AnimationSet, String> {
+  SpriteSheet::parse(&assets.try_text(sheet_filename)?)?.animations(&assets.try_text(filename)?)
+}
 
-  for x in 0..7 {
-    for y in 0..7 {
-      let key = &format!("critical_{}_{}", x, y);
-      sprites.push(get_frame_data(&zombie, key));
-    }
-  }
-  for x in 0..7 {
-    for y in 0..5 {
-      let key = &format!("normal_{}_{}", x, y);
-      sprites.push(get_frame_data(&zombie, key));
-    }
-  }
-  for x in 0..7 {
-    for y in 0..4 {
-      let key = &format!("still_{}_{}", x, y);
-      sprites.push(get_frame_data(&zombie, key));
-    }
+// Errors name the value as name.key
+pub fn read_number(data: &JsonValue, key: &str, name: &str) -> Result<f32, String> {
+  if data[key].is_null() {
+    return Err(format!("Missing {}.{}", name, key));
   }
+  data[key].as_f32().ok_or_else(|| format!("Invalid {}.{} value: {}", name, key, data[key]))
+}
 
-  for x in 0..7 {
-    for y in 0..7 {
-      let key = &format!("walk_{}_{}", x, y);
-      sprites.push(get_frame_data(&zombie, key));
-    }
+pub fn read_non_negative(data: &JsonValue, key: &str, name: &str) -> Result<f32, String> {
+  let value = read_number(data, key, name)?;
+  if value < 0.0 {
+    return Err(format!("Invalid {}.{} value: {}", name, key, value));
   }
-  sprites
+  Ok(value)
 }
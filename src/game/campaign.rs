@@ -0,0 +1,144 @@
+use json;
+use specs;
+use specs::prelude::{ReadExpect, ReadStorage, WriteExpect};
+
+use crate::assets::AssetManager;
+use crate::character::controls::CharacterInputState;
+use crate::game::constants::MAP_FILE_PATH;
+use crate::terrain::map_objects::MapObjects;
+use crate::zombie::waves::WaveSpawner;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelDefinition {
+  pub map: String,
+  pub clear_waves: Option<usize>,
+}
+
+// Levels are played in order, the last one goes on until the player dies
+#[derive(Clone, Debug, PartialEq)]
+pub struct Campaign {
+  levels: Vec<LevelDefinition>,
+}
+
+impl Campaign {
+  pub fn new() -> Campaign {
+    Campaign {
+      levels: vec![LevelDefinition {
+        map: MAP_FILE_PATH.to_string(),
+        clear_waves: None,
+      }],
+    }
+  }
+
+  pub fn load(assets: &mut AssetManager, path: &str) -> Campaign {
+    if !assets.exists(path) {
+      return Campaign::new();
+    }
+    let campaign = Campaign::parse(&assets.text(path))
+      .and_then(|campaign| match campaign.levels.iter().position(|level| !assets.exists(&level.map)) {
+        Some(idx) => Err(format!("Level {} map {} not found", idx + 1, campaign.levels[idx].map)),
+        None => Ok(campaign),
+      });
+    match campaign {
+      Ok(c) => c,
+      Err(e) => panic!("Campaign file {} error: {}", path, e),
+    }
+  }
+
+  pub fn parse(content: &str) -> Result<Campaign, String> {
+    let data = json::parse(content).map_err(|e| e.to_string())?;
+    if !data["levels"].is_array() || data["levels"].is_empty() {
+      return Err("levels should be a non-empty list".to_string());
+    }
+
+    let mut levels = Vec::new();
+    for (idx, level) in data["levels"].members().enumerate() {
+      let map = level["map"].as_str()
+        .ok_or_else(|| format!("Invalid map in level {}: {}", idx + 1, level["map"]))?;
+      let clear_waves = &level["clear_waves"];
+      levels.push(LevelDefinition {
+        map: map.to_string(),
+        clear_waves: if clear_waves.is_null() {
+          None
+        } else {
+          Some(clear_waves.as_usize().filter(|w| *w > 0)
+            .ok_or_else(|| format!("Invalid clear_waves in level {}: {}", idx + 1, clear_waves))?)
+        },
+      });
+    }
+    Ok(Campaign {
+      levels,
+    })
+  }
+
+  pub fn levels(&self) -> &[LevelDefinition] {
+    &self.levels
+  }
+}
+
+impl Default for Campaign {
+  fn default() -> Campaign {
+    Campaign::new()
+  }
+}
+
+// Progress through the campaign, a completed level is replaced by the next one between ticks
+pub struct Level {
+  campaign: Campaign,
+  pub index: usize,
+  pub completed: bool,
+}
+
+impl Level {
+  pub fn new(campaign: Campaign) -> Level {
+    Level {
+      campaign,
+      index: 0,
+      completed: false,
+    }
+  }
+
+  pub fn definition(&self) -> &LevelDefinition {
+    &self.campaign.levels[self.index]
+  }
+
+  pub fn map(&self) -> &str {
+    &self.definition().map
+  }
+
+  pub fn has_next(&self) -> bool {
+    self.index + 1 < self.campaign.levels.len()
+  }
+
+  pub fn advance(&mut self) {
+    self.index = (self.index + 1).min(self.campaign.levels.len() - 1);
+    self.completed = false;
+  }
+
+  pub fn restart(&mut self) {
+    self.index = 0;
+    self.completed = false;
+  }
+}
+
+// Levels end when the player reaches an exit or clears the level waves
+pub struct LevelSystem;
+
+impl<'a> specs::prelude::System<'a> for LevelSystem {
+  type SystemData = (WriteExpect<'a, Level>,
+                     ReadStorage<'a, CharacterInputState>,
+                     ReadExpect<'a, MapObjects>,
+                     ReadExpect<'a, WaveSpawner>);
+
+  fn run(&mut self, (mut level, character_input, map_objects, spawner): Self::SystemData) {
+    use specs::join::Join;
+
+    if level.completed || !level.has_next() {
+      return;
+    }
+    let cleared = level.definition().clear_waves.is_some_and(|waves| spawner.cleared() >= waves);
+    let exited = character_input.join()
+      .any(|ci| map_objects.exits.iter().any(|exit| exit.contains(ci.movement)));
+    level.completed = cleared || exited;
+  }
+}
@@ -0,0 +1,97 @@
+#[test]
+fn campaign_parse_test() {
+  use crate::game::campaign::{Campaign, LevelDefinition};
+
+  let campaign = Campaign::parse(r#"{ "levels": [{ "map": "maps/a.tmx", "clear_waves": 2 }, { "map": "maps/b.tmx" }] }"#)
+    .expect("Campaign should parse");
+  assert_eq!(campaign.levels(), [
+    LevelDefinition { map: "maps/a.tmx".to_string(), clear_waves: Some(2) },
+    LevelDefinition { map: "maps/b.tmx".to_string(), clear_waves: None },
+  ]);
+
+  assert_eq!(Campaign::parse(r#"{ "levels": [] }"#), Err("levels should be a non-empty list".to_string()));
+  assert_eq!(Campaign::parse(r#"{ "levels": [{ "clear_waves": 1 }] }"#), Err("Invalid map in level 1: null".to_string()));
+  assert_eq!(Campaign::parse(r#"{ "levels": [{ "map": "maps/a.tmx", "clear_waves": 0 }] }"#), Err("Invalid clear_waves in level 1: 0".to_string()));
+}
+
+#[test]
+fn shipped_campaign_test() {
+  use crate::assets::AssetManager;
+  use crate::data::load_map_file;
+  use crate::game::campaign::Campaign;
+  use crate::game::constants::CAMPAIGN_JSON_PATH;
+  use crate::terrain::map_objects::MapObjects;
+
+  let mut assets = AssetManager::locate(None);
+  let campaign = Campaign::load(&mut assets, CAMPAIGN_JSON_PATH);
+  let (last, levels) = campaign.levels().split_last().expect("Campaign should have levels");
+  let mut map_objects = |map: &str| MapObjects::from_map(&load_map_file(&mut assets, map))
+    .unwrap_or_else(|e| panic!("Map {} error: {}", map, e));
+
+  assert!(!levels.is_empty(), "Campaign should have more than one level");
+  for level in levels {
+    assert!(!map_objects(&level.map).exits.is_empty() || level.clear_waves.is_some(), "Level {} should have a way to end", level.map);
+  }
+  map_objects(&last.map);
+}
+
+#[test]
+fn level_progression_test() {
+  use specs::{Join, RunNow, world::WorldExt};
+
+  use crate::assets::AssetManager;
+  use crate::character::CharacterDrawable;
+  use crate::game::campaign::{Campaign, Level, LevelSystem};
+  use crate::game::constants::{MAP_FILE_PATH, RESOLUTION_X, RESOLUTION_Y};
+  use crate::gfx_app::init::{next_level, reset_world, setup_world};
+  use crate::graphics::dimensions::Dimensions;
+  use crate::terrain::map_objects::{ExitZone, MapObjects};
+  use crate::zombie::waves::WaveSpawner;
+
+  let mut world = specs::World::new();
+  world.insert(AssetManager::locate(None));
+  setup_world(&mut world, Dimensions::new(RESOLUTION_X as f32, RESOLUTION_Y as f32, 1.0), 1);
+  let campaign = format!(r#"{{ "levels": [{{ "map": "{map}", "clear_waves": 1 }}, {{ "map": "{map}" }}] }}"#, map = MAP_FILE_PATH);
+  world.insert(Level::new(Campaign::parse(&campaign).expect("Campaign should parse")));
+
+  let add_exit = |world: &mut specs::World| {
+    let mut map_objects = world.write_resource::<MapObjects>();
+    let position = map_objects.player_start;
+    map_objects.exits.push(ExitZone { position, radius: 1.0 });
+  };
+
+  let map_exits = world.read_resource::<MapObjects>().exits.len();
+  LevelSystem.run_now(&world);
+  assert!(!world.read_resource::<Level>().completed, "Level should go on until the player reaches an exit");
+
+  add_exit(&mut world);
+  LevelSystem.run_now(&world);
+  assert!(world.read_resource::<Level>().completed, "Player standing on the exit should complete the level");
+
+  for c in (&mut world.write_storage::<CharacterDrawable>()).join() {
+    c.stats.ammunition = 3;
+  }
+  next_level(&mut world, 2);
+  assert_eq!(world.read_resource::<Level>().index, 1);
+  assert!(!world.read_resource::<Level>().completed);
+  assert_eq!(world.read_resource::<MapObjects>().exits.len(), map_exits, "Map objects should be loaded from the next map");
+  assert_eq!(world.read_storage::<CharacterDrawable>().join().map(|c| c.stats.ammunition).collect::<Vec<_>>(), vec![3], "Player stats should carry over");
+
+  add_exit(&mut world);
+  LevelSystem.run_now(&world);
+  assert!(!world.read_resource::<Level>().completed, "Last level should not end");
+
+  reset_world(&mut world, 3);
+  assert_eq!(world.read_resource::<Level>().index, 0, "Restart should begin from the first level");
+
+  {
+    let mut spawner = world.write_resource::<WaveSpawner>();
+    spawner.update(0.0, false);
+    while spawner.pending() > 0 {
+      spawner.spawned();
+    }
+    spawner.update(0.0, false);
+  }
+  LevelSystem.run_now(&world);
+  assert!(world.read_resource::<Level>().completed, "Clearing the level waves should complete the level");
+}
@@ -0,0 +1,276 @@
+use std::fmt::{Display, Formatter};
+use std::mem;
+use std::str::FromStr;
+
+use crossbeam_channel as channel;
+use specs;
+use specs::prelude::{Entities, LazyUpdate, Read, ReadExpect, Write, WriteStorage};
+
+use crate::character::{CharacterDrawable, controls::CharacterInputState};
+use crate::critter::CritterAnimations;
+use crate::game::constants::CONSOLE_HISTORY;
+use crate::game::GameRng;
+use crate::gfx_app::controls::ControlEvent;
+use crate::graphics::can_move_to_tile;
+use crate::shaders::Position;
+use crate::terrain::collision::CollisionGrid;
+use crate::zombie::{build_zombie, ZombieKind};
+
+const HELP_TEXT: &str = "Commands: god [on|off], give ammo|magazines <n>, spawn zombie <x> <y>, tp <tile_x> <tile_y>, timescale <scale>, fps, clear";
+
+pub struct DevSettings {
+  pub god_mode: bool,
+  pub show_fps: bool,
+  pub time_scale: f64,
+}
+
+impl DevSettings {
+  pub fn new() -> DevSettings {
+    DevSettings {
+      god_mode: cfg!(feature = "godmode"),
+      show_fps: cfg!(feature = "framerate"),
+      time_scale: 1.0,
+    }
+  }
+}
+
+impl Default for DevSettings {
+  fn default() -> DevSettings {
+    DevSettings::new()
+  }
+}
+
+pub struct Console {
+  pub is_open: bool,
+  pub input: String,
+  pub lines: Vec<String>,
+}
+
+impl Console {
+  pub fn new() -> Console {
+    Console {
+      is_open: false,
+      input: String::new(),
+      lines: Vec::new(),
+    }
+  }
+
+  pub fn print(&mut self, line: String) {
+    self.lines.push(line);
+    if self.lines.len() > CONSOLE_HISTORY {
+      self.lines.remove(0);
+    }
+  }
+}
+
+impl Default for Console {
+  fn default() -> Console {
+    Console::new()
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConsoleCommand {
+  Help,
+  Clear,
+  God(Option<bool>),
+  GiveAmmo(usize),
+  GiveMagazines(usize),
+  SpawnZombie(f32, f32),
+  Teleport(i32, i32),
+  TimeScale(f64),
+  Fps,
+}
+
+fn parse_arg<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+  value.parse::<T>().map_err(|_| format!("Invalid {} value: {}", name, value))
+}
+
+// Displays commands the way they are typed, replay files store them as text
+impl Display for ConsoleCommand {
+  fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    match self {
+      ConsoleCommand::Help => write!(f, "help"),
+      ConsoleCommand::Clear => write!(f, "clear"),
+      ConsoleCommand::God(None) => write!(f, "god"),
+      ConsoleCommand::God(Some(enabled)) => write!(f, "god {}", if *enabled { "on" } else { "off" }),
+      ConsoleCommand::GiveAmmo(n) => write!(f, "give ammo {}", n),
+      ConsoleCommand::GiveMagazines(n) => write!(f, "give magazines {}", n),
+      ConsoleCommand::SpawnZombie(x, y) => write!(f, "spawn zombie {} {}", x, y),
+      ConsoleCommand::Teleport(x, y) => write!(f, "tp {} {}", x, y),
+      ConsoleCommand::TimeScale(scale) => write!(f, "timescale {}", scale),
+      ConsoleCommand::Fps => write!(f, "fps"),
+    }
+  }
+}
+
+impl FromStr for ConsoleCommand {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<ConsoleCommand, String> {
+    let parts = s.split_whitespace().collect::<Vec<&str>>();
+    match parts.as_slice() {
+      ["help"] => Ok(ConsoleCommand::Help),
+      ["clear"] => Ok(ConsoleCommand::Clear),
+      ["god"] => Ok(ConsoleCommand::God(None)),
+      ["god", "on"] => Ok(ConsoleCommand::God(Some(true))),
+      ["god", "off"] => Ok(ConsoleCommand::God(Some(false))),
+      ["give", "ammo", n] => Ok(ConsoleCommand::GiveAmmo(parse_arg("ammo", n)?)),
+      ["give", "magazines", n] => Ok(ConsoleCommand::GiveMagazines(parse_arg("magazines", n)?)),
+      ["spawn", "zombie", x, y] => Ok(ConsoleCommand::SpawnZombie(parse_arg("x", x)?, parse_arg("y", y)?)),
+      ["tp", x, y] => Ok(ConsoleCommand::Teleport(parse_arg("tile_x", x)?, parse_arg("tile_y", y)?)),
+      ["timescale", scale] => match parse_arg::<f64>("timescale", scale)? {
+        scale if scale > 0.0 => Ok(ConsoleCommand::TimeScale(scale)),
+        scale => Err(format!("Invalid timescale value: {}", scale)),
+      },
+      ["fps"] => Ok(ConsoleCommand::Fps),
+      _ => Err(format!("Unknown command: {}, type help for commands", s.trim())),
+    }
+  }
+}
+
+pub enum ConsoleControl {
+  Toggle,
+  Input(char),
+  Backspace,
+  Submit,
+}
+
+// Edits the console input, commands changing the game are recorded and run by ConsoleCommandSystem between ticks
+pub struct ConsoleSystem {
+  queue: channel::Receiver<ConsoleControl>,
+  commands: channel::Sender<ConsoleCommand>,
+  recorder: Option<channel::Sender<ControlEvent>>,
+}
+
+impl ConsoleSystem {
+  pub fn new(commands: channel::Sender<ConsoleCommand>) -> (ConsoleSystem, channel::Sender<ConsoleControl>) {
+    let (tx, rx) = channel::unbounded();
+    (ConsoleSystem {
+      queue: rx,
+      commands,
+      recorder: None,
+    }, tx)
+  }
+
+  pub fn set_recorder(&mut self, recorder: channel::Sender<ControlEvent>) {
+    self.recorder = Some(recorder);
+  }
+}
+
+impl<'a> specs::prelude::System<'a> for ConsoleSystem {
+  type SystemData = (Write<'a, Console>,
+                     Write<'a, DevSettings>);
+
+  fn run(&mut self, (mut console, mut dev): Self::SystemData) {
+    while let Ok(control) = self.queue.try_recv() {
+      let input = match control {
+        ConsoleControl::Toggle => {
+          console.is_open = !console.is_open;
+          continue;
+        }
+        ConsoleControl::Input(c) => {
+          console.input.push(c);
+          continue;
+        }
+        ConsoleControl::Backspace => {
+          console.input.pop();
+          continue;
+        }
+        ConsoleControl::Submit => mem::take(&mut console.input),
+      };
+
+      if input.trim().is_empty() {
+        continue;
+      }
+      console.print(format!("> {}", input));
+
+      match input.parse::<ConsoleCommand>() {
+        Ok(ConsoleCommand::Help) => console.print(HELP_TEXT.to_string()),
+        Ok(ConsoleCommand::Clear) => console.lines.clear(),
+        Ok(ConsoleCommand::Fps) => {
+          dev.show_fps = !dev.show_fps;
+          console.print(format!("Frame rate {}", if dev.show_fps { "shown" } else { "hidden" }));
+        }
+        Ok(command) => {
+          if let Some(ref recorder) = self.recorder {
+            recorder.send(ControlEvent::Console(command)).expect("Console recorder update error");
+          }
+          self.commands.send(command).expect("Console command update error");
+        }
+        Err(e) => console.print(e),
+      }
+    }
+  }
+}
+
+pub struct ConsoleCommandSystem {
+  queue: channel::Receiver<ConsoleCommand>,
+}
+
+impl ConsoleCommandSystem {
+  pub fn new() -> (ConsoleCommandSystem, channel::Sender<ConsoleCommand>) {
+    let (tx, rx) = channel::unbounded();
+    (ConsoleCommandSystem {
+      queue: rx,
+    }, tx)
+  }
+}
+
+impl<'a> specs::prelude::System<'a> for ConsoleCommandSystem {
+  type SystemData = (Write<'a, Console>,
+                     Write<'a, DevSettings>,
+                     WriteStorage<'a, CharacterDrawable>,
+                     WriteStorage<'a, CharacterInputState>,
+                     Write<'a, GameRng>,
+                     ReadExpect<'a, CritterAnimations>,
+                     ReadExpect<'a, CollisionGrid>,
+                     Entities<'a>,
+                     Read<'a, LazyUpdate>);
+
+  fn run(&mut self, (mut console, mut dev, mut character, mut character_input, mut rng, animations, collision, entities, lazy): Self::SystemData) {
+    use specs::join::Join;
+
+    while let Ok(command) = self.queue.try_recv() {
+      let output = match command {
+        ConsoleCommand::Help | ConsoleCommand::Clear | ConsoleCommand::Fps => continue,
+        ConsoleCommand::God(enabled) => {
+          dev.god_mode = enabled.unwrap_or(!dev.god_mode);
+          format!("God mode {}", if dev.god_mode { "on" } else { "off" })
+        }
+        ConsoleCommand::GiveAmmo(ammunition) => {
+          for c in (&mut character).join() {
+            c.stats.ammunition = ammunition;
+          }
+          format!("Ammo set to {}", ammunition)
+        }
+        ConsoleCommand::GiveMagazines(magazines) => {
+          for c in (&mut character).join() {
+            c.stats.magazines = magazines;
+          }
+          format!("Magazines set to {}", magazines)
+        }
+        ConsoleCommand::SpawnZombie(x, y) => {
+          build_zombie(lazy.create_entity(&entities), Position::new(x, y), &ZombieKind::new(), &animations.zombie, GameRng::new(rng.next_seed()));
+          format!("Zombie spawned at {}, {}", x, y)
+        }
+        ConsoleCommand::Teleport(x, y) => {
+          let target = collision.size().tile_to_coords(x, y);
+          if can_move_to_tile(&collision, target) {
+            for ci in (&mut character_input).join() {
+              ci.movement = target;
+            }
+            format!("Teleported to tile {}, {}", x, y)
+          } else {
+            format!("Tile {}, {} is not walkable", x, y)
+          }
+        }
+        ConsoleCommand::TimeScale(scale) => {
+          dev.time_scale = scale;
+          format!("Time scale set to {}", scale)
+        }
+      };
+      console.print(output);
+    }
+  }
+}
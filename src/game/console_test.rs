@@ -0,0 +1,71 @@
+#[test]
+fn console_command_parse_test() {
+  use crate::game::console::ConsoleCommand;
+
+  assert_eq!("god on".parse::<ConsoleCommand>(), Ok(ConsoleCommand::God(Some(true))));
+  assert_eq!("god".parse::<ConsoleCommand>(), Ok(ConsoleCommand::God(None)));
+  assert_eq!("give ammo 50".parse::<ConsoleCommand>(), Ok(ConsoleCommand::GiveAmmo(50)));
+  assert_eq!("spawn zombie 100 -20.5".parse::<ConsoleCommand>(), Ok(ConsoleCommand::SpawnZombie(100.0, -20.5)));
+  assert_eq!("tp 60 70".parse::<ConsoleCommand>(), Ok(ConsoleCommand::Teleport(60, 70)));
+  assert_eq!("timescale 0.5".parse::<ConsoleCommand>(), Ok(ConsoleCommand::TimeScale(0.5)));
+  assert!("timescale 0".parse::<ConsoleCommand>().is_err());
+  assert!("give ammo lots".parse::<ConsoleCommand>().is_err());
+  assert!("noclip".parse::<ConsoleCommand>().is_err());
+
+  for command in &[ConsoleCommand::God(Some(false)), ConsoleCommand::GiveMagazines(2), ConsoleCommand::SpawnZombie(100.25, -20.5), ConsoleCommand::TimeScale(0.1)] {
+    assert_eq!(command.to_string().parse::<ConsoleCommand>(), Ok(*command), "Commands should be typed back as displayed");
+  }
+}
+
+#[test]
+fn console_replay_event_test() {
+  use crate::game::console::ConsoleCommand;
+  use crate::gfx_app::controls::ControlEvent;
+
+  let event = ControlEvent::Console(ConsoleCommand::SpawnZombie(100.0, -20.5));
+  assert_eq!(event.to_string(), "console spawn zombie 100 -20.5");
+  assert_eq!(event.to_string().parse::<ControlEvent>(), Ok(event));
+  assert!("console".parse::<ControlEvent>().is_err());
+  assert!("console jump".parse::<ControlEvent>().is_err());
+}
+
+#[test]
+fn console_system_test() {
+  use specs::{Join, RunNow, world::WorldExt};
+
+  use crate::assets::AssetManager;
+  use crate::character::{CharacterDrawable, controls::CharacterInputState};
+  use crate::game::console::{Console, ConsoleCommandSystem, ConsoleControl, ConsoleSystem, DevSettings};
+  use crate::game::constants::{RESOLUTION_X, RESOLUTION_Y};
+  use crate::gfx_app::init::setup_world;
+  use crate::graphics::dimensions::Dimensions;
+  use crate::terrain::collision::CollisionGrid;
+  use crate::zombie::AiState;
+
+  let mut world = specs::World::new();
+  world.insert(AssetManager::locate(None));
+  setup_world(&mut world, Dimensions::new(RESOLUTION_X as f32, RESOLUTION_Y as f32, 1.0), 1);
+  let zombie_count = world.read_storage::<AiState>().join().count();
+
+  let (mut command_system, command_control) = ConsoleCommandSystem::new();
+  let (mut console_system, console_control) = ConsoleSystem::new(command_control);
+  for command in &["give ammo 50", "god on", "timescale 0.5", "spawn zombie 100 100", "tp 60 70", "jump"] {
+    for ch in command.chars() {
+      console_control.send(ConsoleControl::Input(ch)).unwrap();
+    }
+    console_control.send(ConsoleControl::Submit).unwrap();
+  }
+  console_system.run_now(&world);
+  assert!(!world.read_resource::<DevSettings>().god_mode, "Commands should wait for the next tick");
+  command_system.run_now(&world);
+  world.maintain();
+
+  assert_eq!(world.read_storage::<CharacterDrawable>().join().map(|c| c.stats.ammunition).collect::<Vec<_>>(), vec![50]);
+  assert!(world.read_resource::<DevSettings>().god_mode);
+  assert_eq!(world.read_resource::<DevSettings>().time_scale, 0.5);
+  assert_eq!(world.read_storage::<AiState>().join().count(), zombie_count + 1);
+  let size = world.read_resource::<CollisionGrid>().size();
+  let tiles = world.read_storage::<CharacterInputState>().join().map(|ci| size.coords_to_tile(ci.movement)).collect::<Vec<_>>();
+  assert_eq!(tiles, vec![cgmath::Point2::new(60, 70)]);
+  assert!(world.read_resource::<Console>().lines.iter().any(|l| l.starts_with("Unknown command")));
+}
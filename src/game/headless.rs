@@ -0,0 +1,203 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Display, Formatter, Result};
+use std::hash::Hasher;
+
+use crossbeam_channel as channel;
+use specs::{Join, prelude::DispatcherBuilder, shred::World, world::WorldExt};
+
+use crate::assets::AssetManager;
+use crate::bullet::bullets::Bullets;
+use crate::character::{CharacterDrawable, controls::{CharacterControlSystem, CharacterInputState}};
+use crate::game::console::ConsoleCommandSystem;
+use crate::game::constants::{RESOLUTION_X, RESOLUTION_Y};
+use crate::game::GameRng;
+use crate::game::state::GameState;
+use crate::gfx_app::controls::TilemapControls;
+use crate::game::campaign::Level;
+use crate::gfx_app::init::{is_player_dead, next_level, reset_world, setup_world, with_game_systems};
+use crate::gfx_app::mouse_controls::MouseControlSystem;
+use crate::gfx_app::replay::Replay;
+use crate::graphics::{DeltaTime, dimensions::Dimensions, GameTime};
+use crate::graphics::camera::CameraControlSystem;
+use crate::shaders::Position;
+use crate::zombie::AiState;
+
+pub struct HeadlessOptions {
+  pub ticks: u64,
+  pub tick_rate: f64,
+  pub seed: u64,
+  pub dimensions: Dimensions,
+  pub replay: Option<Replay>,
+  pub assets: Option<String>,
+}
+
+impl HeadlessOptions {
+  pub fn new(ticks: u64, tick_rate: f64, seed: u64) -> HeadlessOptions {
+    HeadlessOptions {
+      ticks,
+      tick_rate,
+      seed,
+      dimensions: Dimensions::new(RESOLUTION_X as f32, RESOLUTION_Y as f32, 1.0),
+      replay: None,
+      assets: None,
+    }
+  }
+
+  pub fn from_replay(ticks: Option<u64>, replay: Replay) -> HeadlessOptions {
+    HeadlessOptions {
+      ticks: ticks.unwrap_or_else(|| replay.ticks()),
+      tick_rate: replay.header.tick_rate,
+      seed: replay.header.seed,
+      dimensions: replay.header.dimensions(),
+      replay: Some(replay),
+      assets: None,
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationSummary {
+  pub seed: u64,
+  pub ticks: u64,
+  pub level: usize,
+  pub player_alive: bool,
+  pub player_position: [f32; 2],
+  pub ammunition: usize,
+  pub magazines: usize,
+  pub zombies_alive: usize,
+  pub zombies_dead: usize,
+  pub bullets_in_flight: usize,
+  pub checksum: u64,
+}
+
+impl SimulationSummary {
+  fn from_world(world: &World, ticks: u64) -> SimulationSummary {
+    let character = world.read_storage::<CharacterDrawable>();
+    let character_input = world.read_storage::<CharacterInputState>();
+    let positions = world.read_storage::<Position>();
+    let ai_states = world.read_storage::<AiState>();
+    let bullets = world.read_storage::<Bullets>();
+
+    let mut summary = SimulationSummary {
+      seed: world.read_resource::<GameRng>().seed(),
+      ticks,
+      level: world.read_resource::<Level>().index + 1,
+      player_alive: !is_player_dead(world),
+      player_position: Position::origin().position,
+      ammunition: 0,
+      magazines: 0,
+      zombies_alive: 0,
+      zombies_dead: 0,
+      bullets_in_flight: 0,
+      checksum: 0,
+    };
+    let mut hasher = DefaultHasher::new();
+
+    for (cd, ci, bs) in (&character, &character_input, &bullets).join() {
+      summary.player_position = ci.movement.position;
+      summary.ammunition = cd.stats.ammunition;
+      summary.magazines = cd.stats.magazines;
+      summary.bullets_in_flight = bs.bullets.len();
+
+      hash_position(&mut hasher, ci.movement);
+      for b in &bs.bullets {
+        hash_position(&mut hasher, b.position);
+      }
+    }
+    for (position, ai) in (&positions, &ai_states).join() {
+      if ai.is_alive() {
+        summary.zombies_alive += 1;
+      } else {
+        summary.zombies_dead += 1;
+      }
+      hash_position(&mut hasher, *position);
+      hasher.write_u8(ai.stance.clone() as u8);
+    }
+    summary.checksum = hasher.finish();
+    summary
+  }
+}
+
+fn hash_position(hasher: &mut DefaultHasher, position: Position) {
+  hasher.write_u32(position.x().to_bits());
+  hasher.write_u32(position.y().to_bits());
+}
+
+impl Display for SimulationSummary {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "seed={} ticks={} level={} player_alive={} player_position={}, {} ammunition={} magazines={} zombies_alive={} zombies_dead={} bullets_in_flight={} checksum={:016x}",
+           self.seed,
+           self.ticks,
+           self.level,
+           self.player_alive,
+           self.player_position[0],
+           self.player_position[1],
+           self.ammunition,
+           self.magazines,
+           self.zombies_alive,
+           self.zombies_dead,
+           self.bullets_in_flight,
+           self.checksum)
+  }
+}
+
+pub fn run(options: HeadlessOptions) -> SimulationSummary {
+  let mut w: World = WorldExt::new();
+  w.insert(AssetManager::locate(options.assets.as_deref()));
+  setup_world(&mut w, options.dimensions.clone(), options.seed);
+  *w.write_resource::<GameState>() = GameState::Playing;
+
+  let (audio_control, audio_queue) = channel::unbounded();
+  let (camera_system, camera_control) = CameraControlSystem::new();
+  let (character_system, character_control) = CharacterControlSystem::new();
+  let (mouse_system, mouse_control) = MouseControlSystem::new();
+  let (game_state_control, _game_state_queue) = channel::unbounded();
+  let (console_control, _console_queue) = channel::unbounded();
+  let (command_system, command_control) = ConsoleCommandSystem::new();
+  let mut controls = TilemapControls::new(audio_control, camera_control, character_control, mouse_control, game_state_control, console_control, command_control);
+  let mut replay = options.replay;
+
+  let mut dispatcher = with_game_systems(DispatcherBuilder::new(),
+                                         camera_system,
+                                         character_system,
+                                         mouse_system,
+                                         command_system)
+    .build();
+
+  let tick_delta = 1.0 / options.tick_rate;
+  *w.write_resource::<DeltaTime>() = DeltaTime(tick_delta);
+
+  let mut ticks = 0;
+  let mut round_start = 0;
+  while ticks < options.ticks {
+    if let Some(ref mut r) = replay {
+      r.apply(ticks, &mut controls);
+    }
+    while audio_queue.try_recv().is_ok() {}
+
+    dispatcher.dispatch(&w);
+    w.maintain();
+    ticks += 1;
+    if w.read_resource::<Level>().completed {
+      let seed = w.write_resource::<GameRng>().next_seed();
+      next_level(&mut w, seed);
+    }
+
+    *w.write_resource::<GameTime>() = GameTime::from_ticks(ticks - round_start, tick_delta);
+
+    if is_player_dead(&w) {
+      match replay.as_ref().and_then(|r| r.restart_seed(ticks)) {
+        Some(seed) => {
+          reset_world(&mut w, seed);
+          round_start = ticks;
+        }
+        None => {
+          *w.write_resource::<GameState>() = GameState::GameOver;
+          break;
+        }
+      }
+    }
+  }
+
+  SimulationSummary::from_world(&w, ticks)
+}
@@ -0,0 +1,95 @@
+#[test]
+fn headless_simulation_test() {
+  use crate::assets::AssetManager;
+  use crate::game::constants::{DEFAULT_TICK_RATE, WAVES_JSON_PATH};
+  use crate::game::headless::{HeadlessOptions, run};
+  use crate::zombie::waves::WaveDefinitions;
+
+  let summary = run(HeadlessOptions::new(120, DEFAULT_TICK_RATE, 1));
+
+  assert_eq!(summary.ticks, 120, "Idle player should survive the first second");
+  assert!(summary.player_alive, "Player should be alive");
+  assert_eq!(summary.zombies_alive, WaveDefinitions::load(&mut AssetManager::locate(None), WAVES_JSON_PATH).wave(0).count, "First wave should spawn and no zombie should die without shooting");
+  assert_eq!(summary.ammunition, 10, "No ammunition should be used");
+}
+
+#[test]
+fn headless_simulation_determinism_test() {
+  use crate::game::constants::DEFAULT_TICK_RATE;
+  use crate::game::headless::{HeadlessOptions, run};
+
+  let first = run(HeadlessOptions::new(600, DEFAULT_TICK_RATE, 42));
+  let second = run(HeadlessOptions::new(600, DEFAULT_TICK_RATE, 42));
+  let other_seed = run(HeadlessOptions::new(600, DEFAULT_TICK_RATE, 43));
+
+  assert_eq!(first, second, "Same seed should produce identical simulations");
+  assert_ne!(first.checksum, other_seed.checksum, "Different seeds should diverge");
+}
+
+#[test]
+fn headless_replay_test() {
+  use crate::game::headless::{HeadlessOptions, run};
+  use crate::gfx_app::replay::Replay;
+
+  let replay = "hinterland-replay 1\n\
+                seed 42\n\
+                tick_rate 120\n\
+                viewport 1920 1080 1\n\
+                10 character ctrl_pressed\n\
+                20 click 1200 300\n\
+                21 click release\n\
+                30 character left\n\
+                90 character x_stop\n\
+                end 120\n"
+    .parse::<Replay>()
+    .expect("Replay should parse");
+
+  let first = run(HeadlessOptions::from_replay(None, replay));
+
+  assert_eq!(first.ticks, 120, "Replay should run until its end tick");
+  assert_eq!(first.seed, 42, "Replay should use recorded seed");
+  assert_eq!(first.ammunition, 9, "Replayed shot should use ammunition");
+  assert!(first.player_position[0] > 0.0, "Replayed movement should move player");
+}
+
+#[test]
+fn headless_replay_restart_test() {
+  use crate::game::constants::DEFAULT_TICK_RATE;
+  use crate::game::headless::{HeadlessOptions, run};
+  use crate::gfx_app::replay::Replay;
+
+  let idle = run(HeadlessOptions::new(10_000, DEFAULT_TICK_RATE, 42));
+  assert!(!idle.player_alive, "Idle player should die");
+
+  let replay = format!("hinterland-replay 1\nseed 42\ntick_rate {}\nviewport 1920 1080 1\n{} restart 7\nend {}\n",
+                       DEFAULT_TICK_RATE, idle.ticks, idle.ticks + 120)
+    .parse::<Replay>()
+    .expect("Replay should parse");
+  let summary = run(HeadlessOptions::from_replay(None, replay));
+
+  assert_eq!(summary.ticks, idle.ticks + 120, "Recorded restart should continue the replay after the player dies");
+  assert!(summary.player_alive, "Player should be alive after restart");
+  assert_eq!(summary.level, 1, "Restart should begin from the first level");
+}
+
+#[test]
+fn headless_replay_console_test() {
+  use crate::game::headless::{HeadlessOptions, run};
+  use crate::gfx_app::replay::Replay;
+
+  let replay = "hinterland-replay 1\n\
+                seed 42\n\
+                tick_rate 120\n\
+                viewport 1920 1080 1\n\
+                5 console give ammo 3\n\
+                6 console spawn zombie 100 100\n\
+                end 10\n"
+    .parse::<Replay>()
+    .expect("Replay should parse");
+
+  let summary = run(HeadlessOptions::from_replay(None, replay));
+  let plain = run(HeadlessOptions::new(10, 120.0, 42));
+
+  assert_eq!(summary.ammunition, 3, "Replayed console command should change ammunition");
+  assert_eq!(summary.zombies_alive, plain.zombies_alive + 1, "Replayed console command should spawn a zombie");
+}
@@ -1,23 +1,63 @@
 use num::Integer;
 use rand;
+use rand::{Rng, SeedableRng};
 use rand::distributions::uniform::SampleUniform;
-use rand::Rng;
+use rand::rngs::StdRng;
 
+pub mod campaign;
+mod campaign_test;
+pub mod console;
+mod console_test;
 pub mod constants;
+pub mod headless;
+mod headless_test;
+pub mod settings;
+mod settings_test;
+pub mod state;
+mod state_test;
 
-pub fn get_random_bool() -> bool {
-  let mut rnd = rand::thread_rng();
-  rnd.gen()
+pub struct GameRng {
+  seed: u64,
+  rng: StdRng,
 }
 
-pub fn get_rand_from_range<T>(min: T, max: T) -> T
-  where T: Integer + SampleUniform {
-  let mut rnd = rand::thread_rng();
-  rnd.gen_range(min, max)
+impl GameRng {
+  pub fn new(seed: u64) -> GameRng {
+    GameRng {
+      seed,
+      rng: StdRng::seed_from_u64(seed),
+    }
+  }
+
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  pub fn next_seed(&mut self) -> u64 {
+    self.rng.gen()
+  }
+
+  pub fn get_random_bool(&mut self) -> bool {
+    self.rng.gen()
+  }
+
+  pub fn get_rand_from_range<T>(&mut self, min: T, max: T) -> T
+    where T: Integer + SampleUniform {
+    self.rng.gen_range(min, max)
+  }
+
+  #[allow(dead_code)]
+  pub fn get_weighted_random(&mut self, weight: f32) -> bool {
+    self.rng.gen::<f32>() < weight
+  }
+}
+
+impl Default for GameRng {
+  fn default() -> GameRng {
+    GameRng::new(random_seed())
+  }
 }
 
-#[allow(dead_code)]
-pub fn get_weighted_random(weight: f32) -> bool {
-  let mut rnd = rand::thread_rng();
-  rnd.gen::<f32>() < weight
+pub fn random_seed() -> u64 {
+  rand::thread_rng().gen()
 }
@@ -0,0 +1,169 @@
+use std::env;
+use std::fmt::{Display, Formatter, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use json;
+use json::JsonValue;
+
+use crate::game::constants::{CORPSE_TIME, DEFAULT_TICK_RATE, GAME_TITLE, MAX_VIEW_DISTANCE, MIN_VIEW_DISTANCE, RESOLUTION_X, RESOLUTION_Y, SETTINGS_FILE_NAME, VIEW_DISTANCE};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+  pub windowed_mode: bool,
+  pub resolution: (u32, u32),
+  pub vsync: bool,
+  pub monitor: usize,
+  pub tick_rate: f64,
+  pub view_distance: f32,
+  pub min_view_distance: f32,
+  pub max_view_distance: f32,
+  pub volume: f32,
+  pub corpse_time: f32,
+}
+
+impl Settings {
+  pub fn new() -> Settings {
+    Settings {
+      windowed_mode: false,
+      resolution: (RESOLUTION_X, RESOLUTION_Y),
+      vsync: true,
+      monitor: 0,
+      tick_rate: DEFAULT_TICK_RATE,
+      view_distance: VIEW_DISTANCE,
+      min_view_distance: MIN_VIEW_DISTANCE,
+      max_view_distance: MAX_VIEW_DISTANCE,
+      volume: 1.0,
+      corpse_time: CORPSE_TIME,
+    }
+  }
+
+  // Unreadable or invalid files fall back to the defaults
+  pub fn load(path: &Path) -> Settings {
+    if !path.exists() {
+      let settings = Settings::new();
+      settings.save(path);
+      return settings;
+    }
+    let settings = fs::read_to_string(path)
+      .map_err(|e| e.to_string())
+      .and_then(|content| Settings::parse(&content));
+    match settings {
+      Ok(s) => s,
+      Err(e) => {
+        println!("Settings file {} error, using defaults: {}", path.display(), e);
+        Settings::new()
+      }
+    }
+  }
+
+  pub fn parse(content: &str) -> std::result::Result<Settings, String> {
+    let data = json::parse(content).map_err(|e| e.to_string())?;
+    let defaults = Settings::new();
+
+    let resolution = if data["resolution"].is_null() {
+      defaults.resolution
+    } else {
+      match (data["resolution"][0].as_u32(), data["resolution"][1].as_u32()) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => (w, h),
+        _ => return Err("resolution should be [width, height]".to_string()),
+      }
+    };
+
+    let mut settings = Settings {
+      windowed_mode: read_value(&data, "windowed_mode", JsonValue::as_bool, defaults.windowed_mode)?,
+      resolution,
+      vsync: read_value(&data, "vsync", JsonValue::as_bool, defaults.vsync)?,
+      monitor: read_value(&data, "monitor", JsonValue::as_usize, defaults.monitor)?,
+      tick_rate: read_value(&data, "tick_rate", JsonValue::as_f64, defaults.tick_rate)?,
+      view_distance: read_value(&data, "view_distance", JsonValue::as_f32, defaults.view_distance)?,
+      min_view_distance: read_value(&data, "min_view_distance", JsonValue::as_f32, defaults.min_view_distance)?,
+      max_view_distance: read_value(&data, "max_view_distance", JsonValue::as_f32, defaults.max_view_distance)?,
+      volume: read_value(&data, "volume", JsonValue::as_f32, defaults.volume)?,
+      corpse_time: read_value(&data, "corpse_time", JsonValue::as_f32, defaults.corpse_time)?,
+    };
+
+    if settings.tick_rate <= 0.0 {
+      return Err(format!("Invalid tick_rate value: {}", settings.tick_rate));
+    }
+    if settings.corpse_time < 0.0 {
+      return Err(format!("Invalid corpse_time value: {}", settings.corpse_time));
+    }
+    if settings.min_view_distance <= 0.0 {
+      return Err(format!("Invalid min_view_distance value: {}", settings.min_view_distance));
+    }
+    if settings.min_view_distance > settings.max_view_distance {
+      return Err("min_view_distance should not be greater than max_view_distance".to_string());
+    }
+    settings.view_distance = settings.view_distance.clamp(settings.min_view_distance, settings.max_view_distance);
+    settings.volume = settings.volume.clamp(0.0, 1.0);
+    Ok(settings)
+  }
+
+  pub fn save(&self, path: &Path) {
+    if let Some(dir) = path.parent() {
+      if let Err(e) = fs::create_dir_all(dir) {
+        println!("Settings directory {} creation failed: {}", dir.display(), e);
+        return;
+      }
+    }
+    let mut data = JsonValue::new_object();
+    data["windowed_mode"] = self.windowed_mode.into();
+    data["resolution"] = vec![self.resolution.0, self.resolution.1].into();
+    data["vsync"] = self.vsync.into();
+    data["monitor"] = self.monitor.into();
+    data["tick_rate"] = self.tick_rate.into();
+    data["view_distance"] = self.view_distance.into();
+    data["min_view_distance"] = self.min_view_distance.into();
+    data["max_view_distance"] = self.max_view_distance.into();
+    data["volume"] = self.volume.into();
+    data["corpse_time"] = self.corpse_time.into();
+    if let Err(e) = fs::write(path, json::stringify_pretty(data, 2)) {
+      println!("Settings file {} write failed: {}", path.display(), e);
+    }
+  }
+
+  pub fn update_file<U>(path: &Path, update: U) where U: FnOnce(&mut Settings) {
+    let mut settings = Settings::load(path);
+    update(&mut settings);
+    settings.save(path);
+  }
+}
+
+impl Default for Settings {
+  fn default() -> Settings {
+    Settings::new()
+  }
+}
+
+impl Display for Settings {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "windowed_mode={}, resolution={}x{}, vsync={}, monitor={}, tick_rate={}, volume={}",
+           self.windowed_mode,
+           self.resolution.0,
+           self.resolution.1,
+           self.vsync,
+           self.monitor,
+           self.tick_rate,
+           self.volume)
+  }
+}
+
+fn read_value<T, R>(data: &JsonValue, key: &str, read: R, default: T) -> std::result::Result<T, String>
+  where R: Fn(&JsonValue) -> Option<T> {
+  if data[key].is_null() {
+    Ok(default)
+  } else {
+    read(&data[key]).ok_or_else(|| format!("Invalid {} value: {}", key, data[key]))
+  }
+}
+
+pub fn default_settings_path() -> PathBuf {
+  let config_dir = env::var_os("XDG_CONFIG_HOME")
+    .filter(|dir| !dir.is_empty())
+    .map(PathBuf::from)
+    .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+    .unwrap_or_else(|| PathBuf::from("."));
+  config_dir.join(GAME_TITLE.to_lowercase()).join(SETTINGS_FILE_NAME)
+}
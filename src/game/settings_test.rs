@@ -0,0 +1,22 @@
+#[test]
+fn settings_parse_test() {
+  use crate::game::settings::Settings;
+
+  let settings = Settings::parse(r#"{ "windowed_mode": true, "resolution": [1280, 720], "volume": 0.5 }"#)
+    .expect("Settings should parse");
+
+  assert!(settings.windowed_mode);
+  assert_eq!(settings.resolution, (1280, 720));
+  assert_eq!(settings.volume, 0.5);
+  assert_eq!(settings.tick_rate, Settings::new().tick_rate);
+
+  let clamped = Settings::parse(r#"{ "volume": 3, "view_distance": 10000, "min_view_distance": 100, "max_view_distance": 500 }"#)
+    .expect("Settings should parse");
+  assert_eq!(clamped.volume, 1.0, "Volume should be clamped");
+  assert_eq!(clamped.view_distance, 500.0, "View distance should be clamped to its range");
+
+  assert!(Settings::parse(r#"{ "tick_rate": 0 }"#).is_err());
+  assert!(Settings::parse(r#"{ "min_view_distance": 0 }"#).is_err());
+  assert!(Settings::parse(r#"{ "resolution": [1280] }"#).is_err());
+  assert!(Settings::parse(r#"{ "vsync": "on" }"#).is_err());
+}
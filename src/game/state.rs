@@ -0,0 +1,70 @@
+use crossbeam_channel as channel;
+use specs;
+use specs::prelude::{ReadStorage, Write};
+
+use crate::character::CharacterDrawable;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GameState {
+  #[default]
+  MainMenu,
+  Playing,
+  Paused,
+  GameOver,
+  Restarting,
+  Exiting,
+}
+
+impl GameState {
+  pub fn handle_control(self, control: GameControl) -> GameState {
+    match (self, control) {
+      (GameState::MainMenu, GameControl::Confirm) => GameState::Playing,
+      (GameState::MainMenu, GameControl::Back) |
+      (GameState::MainMenu, GameControl::Quit) => GameState::Exiting,
+      (GameState::Playing, GameControl::Back) => GameState::Paused,
+      (GameState::Paused, GameControl::Back) |
+      (GameState::Paused, GameControl::Confirm) => GameState::Playing,
+      (GameState::Paused, GameControl::Quit) => GameState::Exiting,
+      (GameState::GameOver, GameControl::Confirm) => GameState::Restarting,
+      (GameState::GameOver, GameControl::Back) |
+      (GameState::GameOver, GameControl::Quit) => GameState::Exiting,
+      (state, _) => state,
+    }
+  }
+}
+
+pub enum GameControl {
+  Back,
+  Confirm,
+  Quit,
+}
+
+pub struct GameStateSystem {
+  queue: channel::Receiver<GameControl>,
+}
+
+impl GameStateSystem {
+  pub fn new() -> (GameStateSystem, channel::Sender<GameControl>) {
+    let (tx, rx) = channel::unbounded();
+    (GameStateSystem {
+      queue: rx,
+    }, tx)
+  }
+}
+
+impl<'a> specs::prelude::System<'a> for GameStateSystem {
+  type SystemData = (Write<'a, GameState>,
+                     ReadStorage<'a, CharacterDrawable>);
+
+  fn run(&mut self, (mut state, character): Self::SystemData) {
+    use specs::join::Join;
+
+    while let Ok(control) = self.queue.try_recv() {
+      *state = state.handle_control(control);
+    }
+
+    if *state == GameState::Playing && character.join().any(|c| c.is_dead()) {
+      *state = GameState::GameOver;
+    }
+  }
+}
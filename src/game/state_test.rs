@@ -0,0 +1,13 @@
+#[test]
+fn game_state_transitions_test() {
+  use crate::game::state::{GameControl, GameState};
+
+  assert_eq!(GameState::MainMenu.handle_control(GameControl::Confirm), GameState::Playing);
+  assert_eq!(GameState::MainMenu.handle_control(GameControl::Back), GameState::Exiting);
+  assert_eq!(GameState::Playing.handle_control(GameControl::Back), GameState::Paused);
+  assert_eq!(GameState::Playing.handle_control(GameControl::Quit), GameState::Playing);
+  assert_eq!(GameState::Paused.handle_control(GameControl::Back), GameState::Playing);
+  assert_eq!(GameState::Paused.handle_control(GameControl::Quit), GameState::Exiting);
+  assert_eq!(GameState::GameOver.handle_control(GameControl::Confirm), GameState::Restarting);
+  assert_eq!(GameState::GameOver.handle_control(GameControl::Quit), GameState::Exiting);
+}
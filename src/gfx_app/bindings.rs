@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use glutin::{MouseButton, VirtualKeyCode};
+use json;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+  MoveUp,
+  MoveDown,
+  MoveLeft,
+  MoveRight,
+  Aim,
+  Fire,
+  Reload,
+  ZoomIn,
+  ZoomOut,
+  Pause,
+  Confirm,
+  Quit,
+  Console,
+}
+
+const ACTIONS: [Action; 13] = [Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight,
+  Action::Aim, Action::Fire, Action::Reload, Action::ZoomIn, Action::ZoomOut,
+  Action::Pause, Action::Confirm, Action::Quit, Action::Console];
+
+// Key names follow glutin VirtualKeyCode variants
+const KEY_NAMES: [&str; 161] = [
+  "Key1", "Key2", "Key3", "Key4", "Key5", "Key6", "Key7", "Key8", "Key9", "Key0", "A", "B", "C", "D", "E",
+  "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z",
+  "Escape", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "F13", "F14", "F15",
+  "F16", "F17", "F18", "F19", "F20", "F21", "F22", "F23", "F24", "Snapshot", "Scroll", "Pause", "Insert",
+  "Home", "Delete", "End", "PageDown", "PageUp", "Left", "Up", "Right", "Down", "Back", "Return", "Space",
+  "Compose", "Caret", "Numlock", "Numpad0", "Numpad1", "Numpad2", "Numpad3", "Numpad4", "Numpad5", "Numpad6",
+  "Numpad7", "Numpad8", "Numpad9", "AbntC1", "AbntC2", "Add", "Apostrophe", "Apps", "At", "Ax", "Backslash",
+  "Calculator", "Capital", "Colon", "Comma", "Convert", "Decimal", "Divide", "Equals", "Grave", "Kana",
+  "Kanji", "LAlt", "LBracket", "LControl", "LShift", "LWin", "Mail", "MediaSelect", "MediaStop", "Minus",
+  "Multiply", "Mute", "MyComputer", "NavigateForward", "NavigateBackward", "NextTrack", "NoConvert",
+  "NumpadComma", "NumpadEnter", "NumpadEquals", "OEM102", "Period", "PlayPause", "Power", "PrevTrack",
+  "RAlt", "RBracket", "RControl", "RShift", "RWin", "Semicolon", "Slash", "Sleep", "Stop", "Subtract",
+  "Sysrq", "Tab", "Underline", "Unlabeled", "VolumeDown", "VolumeUp", "Wake", "WebBack", "WebFavorites",
+  "WebForward", "WebHome", "WebRefresh", "WebSearch", "WebStop", "Yen", "Copy", "Paste", "Cut"];
+
+impl Action {
+  fn from_name(name: &str) -> Option<Action> {
+    ACTIONS.iter().find(|a| format!("{:?}", a) == name).cloned()
+  }
+
+  fn default_inputs(self) -> &'static [&'static str] {
+    match self {
+      Action::MoveUp => &["W"],
+      Action::MoveDown => &["S"],
+      Action::MoveLeft => &["A"],
+      Action::MoveRight => &["D"],
+      Action::Aim => &["LControl", "RControl"],
+      Action::Fire => &["MouseLeft"],
+      Action::Reload => &["R"],
+      Action::ZoomIn => &["Z"],
+      Action::ZoomOut => &["X"],
+      Action::Pause => &["Escape"],
+      Action::Confirm => &["Return"],
+      Action::Quit => &["Q"],
+      Action::Console => &["Grave"],
+    }
+  }
+}
+
+fn is_input_name(name: &str) -> bool {
+  match name {
+    "MouseLeft" | "MouseRight" | "MouseMiddle" => true,
+    _ if name.starts_with("Mouse") => name["Mouse".len()..].parse::<u8>().is_ok(),
+    _ => KEY_NAMES.contains(&name),
+  }
+}
+
+pub struct Bindings {
+  inputs: HashMap<String, Action>,
+}
+
+impl Bindings {
+  pub fn new() -> Bindings {
+    let mut inputs = HashMap::new();
+    for action in ACTIONS.iter() {
+      for input in action.default_inputs() {
+        inputs.insert((*input).to_string(), *action);
+      }
+    }
+    Bindings {
+      inputs,
+    }
+  }
+
+  pub fn load(path: &str) -> Bindings {
+    if !Path::new(path).exists() {
+      return Bindings::new();
+    }
+    let content = match fs::read_to_string(path) {
+      Ok(c) => c,
+      Err(e) => panic!("Bindings file {} read error: {}", path, e),
+    };
+    match Bindings::parse(&content) {
+      Ok(b) => b,
+      Err(e) => panic!("Bindings file {} error: {}", path, e),
+    }
+  }
+
+  pub fn parse(content: &str) -> Result<Bindings, String> {
+    let data = json::parse(content).map_err(|e| e.to_string())?;
+    if !data.is_object() {
+      return Err("Bindings should be an object of action names".to_string());
+    }
+
+    // Listed actions drop their defaults first, so swapping two keys is not a conflict
+    let mut bindings = Bindings::new();
+    let mut rebound = Vec::new();
+    for (name, inputs) in data.entries() {
+      let action = Action::from_name(name).ok_or_else(|| format!("Unknown action {}", name))?;
+      if !inputs.is_array() {
+        return Err(format!("Bindings for {} should be a list of inputs", name));
+      }
+      bindings.inputs.retain(|_, a| *a != action);
+      rebound.push((name, action, inputs));
+    }
+    for (name, action, inputs) in rebound {
+      for input in inputs.members() {
+        let input = input.as_str().ok_or_else(|| format!("Invalid input for {}: {}", name, input))?;
+        if !is_input_name(input) {
+          return Err(format!("Unknown input {} for {}", input, name));
+        }
+        match bindings.inputs.insert(input.to_string(), action) {
+          Some(other) if other != action => return Err(format!("Input {} is bound to both {:?} and {}", input, other, name)),
+          _ => {}
+        }
+      }
+    }
+    Ok(bindings)
+  }
+
+  // Name of the first input bound to the action as shown in menu hints
+  pub fn input_name(&self, action: Action) -> Option<String> {
+    let mut inputs = self.inputs.iter()
+      .filter(|(_, a)| **a == action)
+      .map(|(input, _)| input.as_str())
+      .collect::<Vec<&str>>();
+    inputs.sort_unstable();
+    inputs.first().map(|input| match *input {
+      "Escape" => "Esc".to_string(),
+      "Return" => "Enter".to_string(),
+      "Back" => "Backspace".to_string(),
+      _ if input.len() == 4 && input.starts_with("Key") => input["Key".len()..].to_string(),
+      _ => input.to_string(),
+    })
+  }
+
+  pub fn key_action(&self, key: VirtualKeyCode) -> Option<Action> {
+    self.inputs.get(&format!("{:?}", key)).cloned()
+  }
+
+  pub fn mouse_action(&self, button: MouseButton) -> Option<Action> {
+    let name = match button {
+      MouseButton::Left => "MouseLeft".to_string(),
+      MouseButton::Right => "MouseRight".to_string(),
+      MouseButton::Middle => "MouseMiddle".to_string(),
+      MouseButton::Other(b) => format!("Mouse{}", b),
+    };
+    self.inputs.get(&name).cloned()
+  }
+}
+
+impl Default for Bindings {
+  fn default() -> Bindings {
+    Bindings::new()
+  }
+}
@@ -0,0 +1,53 @@
+#[test]
+fn default_bindings_file_test() {
+  use glutin::{MouseButton, VirtualKeyCode};
+
+  use crate::gfx_app::bindings::{Action, Bindings};
+
+  let bindings = Bindings::parse(include_str!("../../bindings.json")).expect("Default bindings file should parse");
+
+  assert_eq!(bindings.key_action(VirtualKeyCode::W), Some(Action::MoveUp));
+  assert_eq!(bindings.key_action(VirtualKeyCode::LControl), Some(Action::Aim));
+  assert_eq!(bindings.mouse_action(MouseButton::Left), Some(Action::Fire));
+  assert_eq!(bindings.key_action(VirtualKeyCode::F1), None);
+}
+
+#[test]
+fn rebind_action_test() {
+  use glutin::VirtualKeyCode;
+
+  use crate::gfx_app::bindings::{Action, Bindings};
+
+  let bindings = Bindings::parse(r#"{ "MoveUp": ["Z"], "MoveLeft": ["Q"], "ZoomIn": ["W"], "Quit": ["F10"] }"#)
+    .expect("Bindings should parse");
+
+  assert_eq!(bindings.key_action(VirtualKeyCode::Z), Some(Action::MoveUp));
+  assert_eq!(bindings.key_action(VirtualKeyCode::Q), Some(Action::MoveLeft));
+  assert_eq!(bindings.key_action(VirtualKeyCode::W), Some(Action::ZoomIn));
+  assert_eq!(bindings.key_action(VirtualKeyCode::A), None);
+  assert_eq!(bindings.key_action(VirtualKeyCode::S), Some(Action::MoveDown));
+  assert!(Bindings::parse(r#"{ "Jump": ["Space"] }"#).is_err());
+}
+
+#[test]
+fn unknown_input_test() {
+  use crate::gfx_app::bindings::Bindings;
+
+  assert_eq!(Bindings::parse(r#"{ "Fire": ["MouseLeft", "Mouse4"], "Reload": ["Key1", "Space"] }"#).err(), None);
+  assert_eq!(Bindings::parse(r#"{ "Reload": ["Spacebar"] }"#).err(), Some("Unknown input Spacebar for Reload".to_string()));
+  assert_eq!(Bindings::parse(r#"{ "Fire": ["MouseBack"] }"#).err(), Some("Unknown input MouseBack for Fire".to_string()));
+}
+
+#[test]
+fn binding_conflict_test() {
+  use crate::gfx_app::bindings::{Action, Bindings};
+
+  assert_eq!(Bindings::parse(r#"{ "Reload": ["W"] }"#).err(), Some("Input W is bound to both MoveUp and Reload".to_string()));
+  assert_eq!(Bindings::parse(r#"{ "Reload": ["Space"], "Fire": ["Space"] }"#).err(),
+             Some("Input Space is bound to both Reload and Fire".to_string()));
+
+  let swapped = Bindings::parse(r#"{ "Pause": ["Q"], "Quit": ["Escape"] }"#).expect("Swapped keys should parse");
+  assert_eq!(swapped.input_name(Action::Pause), Some("Q".to_string()));
+  assert_eq!(swapped.input_name(Action::Quit), Some("Esc".to_string()));
+  assert_eq!(Bindings::parse(r#"{ "Quit": [] }"#).expect("Unbinding should parse").input_name(Action::Quit), None);
+}
@@ -2,64 +2,122 @@ use crossbeam_channel as channel;
 
 use crate::audio::Effects;
 use crate::character::controls::CharacterControl;
+use crate::game::console::{ConsoleCommand, ConsoleControl};
+use crate::game::state::GameControl;
 use crate::gfx_app::mouse_controls::MouseControl;
 use crate::graphics::camera::CameraControl;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Control {
   Plus,
   Negative,
   Released,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControlEvent {
+  Zoom(Control),
+  Character(CharacterControl),
+  MouseLeftClick(Option<(f64, f64)>),
+  Console(ConsoleCommand),
+}
+
 pub struct TilemapControls {
   audio_control: channel::Sender<Effects>,
   terrain_control: channel::Sender<CameraControl>,
   character_control: channel::Sender<CharacterControl>,
   mouse_control: channel::Sender<(MouseControl, Option<(f64, f64)>)>,
+  game_control: channel::Sender<GameControl>,
+  console_control: channel::Sender<ConsoleControl>,
+  command_control: channel::Sender<ConsoleCommand>,
+  recorder: Option<channel::Sender<ControlEvent>>,
 }
 
 impl TilemapControls {
   pub fn new(atc: channel::Sender<Effects>,
              ttc: channel::Sender<CameraControl>,
              ctc: channel::Sender<CharacterControl>,
-             mtc: channel::Sender<(MouseControl, Option<(f64, f64)>)>) -> TilemapControls {
+             mtc: channel::Sender<(MouseControl, Option<(f64, f64)>)>,
+             gtc: channel::Sender<GameControl>,
+             cnc: channel::Sender<ConsoleControl>,
+             cmc: channel::Sender<ConsoleCommand>) -> TilemapControls {
     TilemapControls {
       audio_control: atc,
       terrain_control: ttc,
       character_control: ctc,
       mouse_control: mtc,
+      game_control: gtc,
+      console_control: cnc,
+      command_control: cmc,
+      recorder: None,
+    }
+  }
+
+  pub fn set_recorder(&mut self, recorder: channel::Sender<ControlEvent>) {
+    self.recorder = Some(recorder);
+  }
+
+  pub fn apply(&mut self, event: ControlEvent) {
+    if let Some(ref recorder) = self.recorder {
+      recorder.send(event.clone()).expect("Control recorder update error");
+    }
+    match event {
+      ControlEvent::Zoom(control) => self.send_zoom(control),
+      ControlEvent::Character(control) => self.send_character(control),
+      ControlEvent::MouseLeftClick(mouse_pos) => self.send_mouse_left_click(mouse_pos),
+      ControlEvent::Console(command) => self.command_control.send(command).expect("Console command update error"),
     }
   }
 
   pub fn zoom(&mut self, control: &Control) {
-    match control {
-      Control::Plus => self.terrain_control.send(CameraControl::ZoomIn),
-      Control::Negative => self.terrain_control.send(CameraControl::ZoomOut),
-      Control::Released => self.terrain_control.send(CameraControl::ZoomStop),
-    }.expect("Terrain control update error");
+    self.apply(ControlEvent::Zoom(*control));
   }
 
   pub fn ctrl_pressed(&mut self, is_ctrl: bool) {
     if is_ctrl {
-      self.character_control.send(CharacterControl::CtrlPressed)
+      self.apply(ControlEvent::Character(CharacterControl::CtrlPressed))
     } else {
-      self.character_control.send(CharacterControl::CtrlReleased)
-    }.expect("Character Ctrl control update error");
+      self.apply(ControlEvent::Character(CharacterControl::CtrlReleased))
+    }
   }
 
   pub fn move_character(&mut self, character_control: CharacterControl) {
-    self.character_control.send(character_control).expect("Character move control update error");
+    self.apply(ControlEvent::Character(character_control));
   }
 
   pub fn reload_weapon(&mut self, is_reloading: bool) {
     if is_reloading {
-      self.character_control.send(CharacterControl::ReloadPressed)
+      self.apply(ControlEvent::Character(CharacterControl::ReloadPressed))
     } else {
-      self.character_control.send(CharacterControl::ReloadReleased)
-    }.expect("Character reload weapon control update error");
+      self.apply(ControlEvent::Character(CharacterControl::ReloadReleased))
+    }
   }
 
   pub fn mouse_left_click(&mut self, mouse_pos: Option<(f64, f64)>) {
+    self.apply(ControlEvent::MouseLeftClick(mouse_pos));
+  }
+
+  pub fn game_state(&mut self, control: GameControl) {
+    self.game_control.send(control).expect("Game state control update error");
+  }
+
+  pub fn console(&mut self, control: ConsoleControl) {
+    self.console_control.send(control).expect("Console control update error");
+  }
+
+  fn send_zoom(&mut self, control: Control) {
+    match control {
+      Control::Plus => self.terrain_control.send(CameraControl::ZoomIn),
+      Control::Negative => self.terrain_control.send(CameraControl::ZoomOut),
+      Control::Released => self.terrain_control.send(CameraControl::ZoomStop),
+    }.expect("Terrain control update error");
+  }
+
+  fn send_character(&mut self, control: CharacterControl) {
+    self.character_control.send(control).expect("Character control update error");
+  }
+
+  fn send_mouse_left_click(&mut self, mouse_pos: Option<(f64, f64)>) {
     self.mouse_control.send((MouseControl::LeftClick, mouse_pos)).expect("Mouse control shoot update error");
     match mouse_pos {
       Some(_) => self.audio_control.send(Effects::PistolFire),
@@ -1,143 +1,415 @@
-use std::time;
+use std::{thread, time};
+use std::sync::Arc;
 
+use crossbeam_channel as channel;
 use gfx;
-use specs::{Builder, prelude::DispatcherBuilder, shred::World, world::WorldExt};
+use specs::{Builder, Join, prelude::{Dispatcher, DispatcherBuilder}, RunNow, shred::World, world::WorldExt};
+use tiled::Map;
 
 use crate::{bullet, terrain_shape};
+use crate::assets::AssetManager;
 use crate::audio::AudioSystem;
 use crate::bullet::bullets::Bullets;
 use crate::bullet::collision::CollisionSystem;
 use crate::character;
 use crate::character::controls::CharacterControlSystem;
-use crate::critter::CharacterSprite;
-use crate::gfx_app::{Window, WindowStatus};
-use crate::gfx_app::controls::TilemapControls;
+use crate::critter::CritterAnimations;
+use crate::data;
+use crate::gfx_app::{GameOptions, Window, WindowStatus};
+use crate::gfx_app::controls::{ControlEvent, TilemapControls};
 use crate::gfx_app::mouse_controls::{MouseControlSystem, MouseInputState};
 use crate::gfx_app::renderer::DeviceRenderer;
+use crate::gfx_app::replay::{Recorder, Replay, ReplayHeader};
 use crate::gfx_app::system::DrawSystem;
 use crate::graphics;
-use crate::graphics::{DeltaTime, dimensions::Dimensions, GameTime};
+use crate::graphics::{DeltaTime, dimensions::Dimensions, GameTime, Interpolation};
+use crate::graphics::animation::{AnimationSystem, Animator};
 use crate::graphics::camera::CameraControlSystem;
+use crate::graphics::spatial_grid::{SpatialIndex, SpatialIndexSystem};
 use crate::hud;
 use crate::terrain;
+use crate::terrain::collision::CollisionGrid;
+use crate::terrain::map_objects::MapObjects;
+use crate::terrain::tile_map::Terrain;
 use crate::terrain_object;
+use crate::terrain_object::terrain_objects::TerrainObjects;
 use crate::zombie;
-use crate::zombie::zombies::Zombies;
-use crate::game::constants::SMALL_HILLS;
+use crate::zombie::waves::{WaveDefinitions, WaveSpawner, WaveSpawnerSystem};
+use crate::shaders::Position;
+use crate::game::constants::{CAMPAIGN_JSON_PATH, MAX_FRAME_DELTA, MAX_FRAME_RATE, WAVES_JSON_PATH};
+use crate::game::GameRng;
+use crate::game::campaign::{Campaign, Level, LevelSystem};
+use crate::game::console::{Console, ConsoleCommandSystem, ConsoleSystem, DevSettings};
+use crate::game::settings::Settings;
+use crate::game::state::{GameState, GameStateSystem};
 
-pub fn run<W, D, F>(window: &mut W)
+pub fn run<W, D, F>(window: &mut W, game_options: &GameOptions, replay: Option<Replay>)
   where W: Window<D, F>,
         D: gfx::Device + 'static,
         F: gfx::Factory<D::Resources>,
         D::CommandBuffer: Send {
 
-  let mut w = WorldExt::new();
+  let mut w: World = WorldExt::new();
+  let dimensions = window_dimensions(window);
+  let settings = &game_options.settings;
+  let recorder = game_options.record.as_ref().map(|path| {
+    Recorder::new(path, &ReplayHeader::new(game_options.seed, settings.tick_rate, &dimensions))
+  });
+  w.insert(settings.clone());
+  w.insert(AssetManager::locate(game_options.assets.as_deref()));
+  setup_world(&mut w, dimensions, game_options.seed);
+  w.insert(hud::MenuHints::new(window.get_bindings()));
+  if replay.is_some() {
+    *w.write_resource::<GameState>() = GameState::Playing;
+  }
+  dispatch_loop(window, &mut w, recorder, replay);
+
+  let resolution = w.read_resource::<Settings>().resolution;
+  if resolution != settings.resolution {
+    Settings::update_file(&game_options.settings_path, |s| s.resolution = resolution);
+  }
+}
+
+fn window_dimensions<W, D, F>(window: &mut W) -> Dimensions
+  where W: Window<D, F>,
+        D: gfx::Device,
+        F: gfx::Factory<D::Resources> {
   let viewport_size = window.get_viewport_size();
-  let dimensions = Dimensions::new(viewport_size.0,
-                                   viewport_size.1,
-                                   window.get_hidpi_factor(),
-                                   window.is_windowed());
-  setup_world(&mut w, dimensions);
-  dispatch_loop(window, &mut w);
+  Dimensions::new(viewport_size.0,
+                  viewport_size.1,
+                  window.get_hidpi_factor())
 }
 
-fn setup_world(world: &mut World, dimensions: Dimensions) {
+// Expects the located AssetManager to be inserted already
+pub fn setup_world(world: &mut World, dimensions: Dimensions, seed: u64) {
   world.register::<terrain::TerrainDrawable>();
   world.register::<graphics::camera::CameraInputState>();
   world.register::<character::CharacterDrawable>();
   world.register::<hud::hud_objects::HudObjects>();
   world.register::<terrain_object::terrain_objects::TerrainObjects>();
   world.register::<terrain_shape::terrain_shape_objects::TerrainShapeObjects>();
-  world.register::<Zombies>();
+  world.register::<Position>();
+  world.register::<zombie::Velocity>();
+  world.register::<zombie::Health>();
+  world.register::<zombie::AiState>();
+  world.register::<zombie::SpriteAnimation>();
+  world.register::<zombie::Renderable>();
+  world.register::<zombie::Corpse>();
   world.register::<Bullets>();
-  world.register::<CharacterSprite>();
+  world.register::<Animator>();
   world.register::<character::controls::CharacterInputState>();
   world.register::<MouseInputState>();
 
+  if !world.has_value::<Settings>() {
+    world.insert(Settings::new());
+  }
   world.insert(dimensions);
-  world.insert(character::controls::CharacterInputState::new());
-  world.insert(MouseInputState::new());
   world.insert(DeltaTime(0.0));
-  world.insert(GameTime(0));
+  world.insert(Console::new());
+  world.insert(DevSettings::new());
+  let animations = CritterAnimations::new(&mut world.write_resource::<AssetManager>());
+  world.insert(animations);
+  let campaign = Campaign::load(&mut world.write_resource::<AssetManager>(), CAMPAIGN_JSON_PATH);
+  world.insert(Level::new(campaign));
+  world.insert(GameState::MainMenu);
+  load_level(world);
+  setup_round(world, seed);
+}
 
-  let mut hills = terrain_shape::terrain_shape_objects::TerrainShapeObjects::new();
+// Restarting begins the campaign again from the first level
+pub fn reset_world(world: &mut World, seed: u64) {
+  world.write_resource::<Level>().restart();
+  load_level(world);
+  world.delete_all();
+  world.maintain();
+  setup_round(world, seed);
+  *world.write_resource::<GameState>() = GameState::Playing;
+}
 
-  for hill in SMALL_HILLS.iter() {
-    hills.small_hill(hill[0], hill[1]);
+// Starts a round on the next map, the player keeps ammunition and magazines
+pub fn next_level(world: &mut World, seed: u64) {
+  let stats = world.read_storage::<character::CharacterDrawable>().join().next().map(|c| c.stats.clone());
+  world.write_resource::<Level>().advance();
+  load_level(world);
+  world.delete_all();
+  world.maintain();
+  setup_round(world, seed);
+  if let Some(stats) = stats {
+    for c in (&mut world.write_storage::<character::CharacterDrawable>()).join() {
+      c.stats = stats.clone();
+    }
   }
+}
+
+// The map file is parsed once, terrain, objects and collision are all built from it
+fn load_level(world: &mut World) {
+  let map_path = world.read_resource::<Level>().map().to_string();
+  let map = data::load_map_file(&mut world.write_resource::<AssetManager>(), &map_path);
+  let (terrain, map_objects, collision) = match level_from_map(&map, &map_path) {
+    Ok(level) => level,
+    Err(e) => panic!("Map {} error: {}", map_path, e),
+  };
+  world.insert(SpatialIndex::new(&map_objects.hills));
+  world.insert(terrain);
+  world.insert(collision);
+  world.insert(map_objects);
+}
+
+fn level_from_map(map: &Map, map_path: &str) -> Result<(Terrain, MapObjects, CollisionGrid), String> {
+  let terrain = Terrain::from_map(map, map_path)?;
+  let map_objects = MapObjects::from_map(map)?;
+  let collision = CollisionGrid::from_map(map, &map_objects)?;
+  Ok((terrain, map_objects, collision))
+}
+
+fn setup_round(world: &mut World, seed: u64) {
+  world.insert(character::controls::CharacterInputState::new());
+  world.insert(MouseInputState::new());
+  world.insert(GameTime(0));
+  world.insert(Interpolation(0.0));
+  world.insert(GameRng::new(seed));
+
+  let view_distance = world.read_resource::<Settings>().view_distance;
+  let character_animations = Arc::clone(&world.read_resource::<CritterAnimations>().character);
+  let (terrain_objects, hills, player_start) = {
+    let map_objects = world.read_resource::<MapObjects>();
+    let mut hills = terrain_shape::terrain_shape_objects::TerrainShapeObjects::new();
+    for hill in &map_objects.hills {
+      hills.small_hill(*hill);
+    }
+    (TerrainObjects::new(map_objects.objects.clone()), hills, map_objects.player_start)
+  };
+  let start_tile = world.read_resource::<CollisionGrid>().size().coords_to_tile(player_start);
+  let mut character_input = character::controls::CharacterInputState::new();
+  character_input.movement = player_start;
 
   world.create_entity()
-    .with(terrain::TerrainDrawable::new())
+    .with(terrain::TerrainDrawable::new(start_tile))
     .with(character::CharacterDrawable::new())
     .with(hud::hud_objects::HudObjects::new())
-    .with(terrain_object::terrain_objects::TerrainObjects::new())
+    .with(terrain_objects)
     .with(hills)
-    .with(Zombies::new())
     .with(Bullets::new())
-    .with(CharacterSprite::new())
-    .with(graphics::camera::CameraInputState::new())
-    .with(character::controls::CharacterInputState::new())
+    .with(Animator::new(character_animations, "run"))
+    .with(graphics::camera::CameraInputState::new(view_distance, player_start))
+    .with(character_input)
     .with(MouseInputState::new()).build();
+
+  let waves = WaveDefinitions::load(&mut world.write_resource::<AssetManager>(), WAVES_JSON_PATH);
+  world.insert(WaveSpawner::new(waves));
+}
+
+fn game_dispatchers<'a, 'b>(volume: f32,
+                            assets: &mut AssetManager,
+                            recorder_control: Option<channel::Sender<ControlEvent>>) -> (Dispatcher<'a, 'b>, Dispatcher<'a, 'b>, TilemapControls) {
+  let (audio_system, audio_control) = AudioSystem::new(volume, assets);
+  let (terrain_system, terrain_control) = CameraControlSystem::new();
+  let (character_system, character_control) = CharacterControlSystem::new();
+  let (mouse_system, mouse_control) = MouseControlSystem::new();
+  let (game_state_system, game_state_control) = GameStateSystem::new();
+  let (command_system, command_control) = ConsoleCommandSystem::new();
+  let (mut console_system, console_control) = ConsoleSystem::new(command_control.clone());
+  let mut controls = TilemapControls::new(audio_control, terrain_control, character_control, mouse_control, game_state_control, console_control, command_control);
+  if let Some(recorder_control) = recorder_control {
+    console_system.set_recorder(recorder_control.clone());
+    controls.set_recorder(recorder_control);
+  }
+
+  let ui_dispatcher = DispatcherBuilder::new()
+    .with(console_system, "console-system", &[])
+    .with(game_state_system, "game-state-system", &[])
+    .with(hud::PreDrawSystem, "draw-prep-hud", &["console-system", "game-state-system"])
+    .build();
+
+  let logic_dispatcher_builder = DispatcherBuilder::new()
+    .with(audio_system, "audio-system", &[]);
+
+  let logic_dispatcher = with_game_systems(logic_dispatcher_builder,
+                                           terrain_system,
+                                           character_system,
+                                           mouse_system,
+                                           command_system)
+    .build();
+
+  (ui_dispatcher, logic_dispatcher, controls)
 }
 
 fn dispatch_loop<W, D, F>(window: &mut W,
-                          w: &mut World)
+                          w: &mut World,
+                          recorder: Option<(Recorder, channel::Sender<ControlEvent>)>,
+                          mut replay: Option<Replay>)
   where W: Window<D, F>,
         D: gfx::Device + 'static,
         F: gfx::Factory<D::Resources>,
         D::CommandBuffer: Send {
   let (mut device_renderer, encoder_queue) = DeviceRenderer::new(window.create_buffers(2));
-  let draw = {
+  let (mut draw, views_control) = {
     let rtv = window.get_render_target_view();
     let dsv = window.get_depth_stencil_view();
-    DrawSystem::new(window.get_factory(), &rtv, &dsv, encoder_queue)
+    DrawSystem::new(window.get_factory(), &mut w.write_resource::<AssetManager>(), &w.read_resource::<Terrain>(), &rtv, &dsv, encoder_queue)
   };
+  RunNow::setup(&mut draw, w);
 
-  let (audio_system, audio_control) = AudioSystem::new();
-  let (terrain_system, terrain_control) = CameraControlSystem::new();
-  let (character_system, character_control) = CharacterControlSystem::new();
-  let (mouse_system, mouse_control) = MouseControlSystem::new();
-  let controls = TilemapControls::new(audio_control, terrain_control, character_control, mouse_control);
-
-  let mut dispatcher = DispatcherBuilder::new()
-    .with(draw, "drawing", &[])
-    .with(terrain::PreDrawSystem, "draw-prep-terrain", &["drawing"])
-    .with(character::PreDrawSystem, "draw-prep-character", &["drawing"])
-    .with(zombie::PreDrawSystem, "draw-prep-zombie", &["drawing"])
-    .with(bullet::PreDrawSystem, "draw-prep-bullet", &["drawing"])
-    .with(hud::PreDrawSystem, "draw-prep-hud", &[])
-    .with(terrain_system, "terrain-system", &[])
-    .with(terrain_object::PreDrawSystem, "draw-prep-terrain_object", &["terrain-system"])
-    .with(terrain_shape::PreDrawSystem, "draw-prep-terrain_shape_object", &["terrain-system"])
-    .with(character_system, "character-system", &[])
-    .with(mouse_system, "mouse-system", &[])
-    .with(audio_system, "audio-system", &[])
-    .with(CollisionSystem, "collision-system", &["mouse-system"])
-    .build();
+  let (tick_rate, volume) = {
+    let settings = w.read_resource::<Settings>();
+    (settings.tick_rate, settings.volume)
+  };
 
-  window.set_controls(controls);
+  let recorder_control = recorder.as_ref().map(|(_, recorder_control)| recorder_control.clone());
+  let (mut ui_dispatcher, mut logic_dispatcher, controls) = game_dispatchers(volume, &mut w.write_resource::<AssetManager>(), recorder_control);
+  let mut recorder = recorder.map(|(recorder, _)| recorder);
 
-  let start_time = time::Instant::now();
+  let mut replay_controls = None;
+  if replay.is_some() {
+    replay_controls = Some(controls);
+  } else {
+    window.set_controls(controls);
+  }
+
+  let tick_delta = 1.0 / tick_rate;
+  let min_frame_time = time::Duration::from_secs_f64(1.0 / MAX_FRAME_RATE);
+  *w.write_resource::<DeltaTime>() = DeltaTime(tick_delta);
+
+  let mut animation = AnimationSystem;
+  let mut ticks = 0;
+  let mut round_start = 0;
+  let mut accumulator = 0.0;
   let mut last_time = time::Instant::now();
   loop {
-    let elapsed = last_time.elapsed();
-    let delta = f64::from(elapsed.subsec_nanos()) / 1e9 + elapsed.as_secs() as f64;
-    // Throttle update speed
-    if delta >= 0.0083 {
-      last_time = time::Instant::now();
-      dispatcher.dispatch(&w);
-      w.maintain();
+    let frame_start = time::Instant::now();
+    let time_scale = w.read_resource::<DevSettings>().time_scale;
+    accumulator += frame_start.duration_since(last_time).as_secs_f64().min(MAX_FRAME_DELTA) * time_scale;
+    last_time = frame_start;
+
+    ui_dispatcher.dispatch(w);
+    let state = *w.read_resource::<GameState>();
 
-      *w.write_resource::<DeltaTime>() = DeltaTime(delta);
-      *w.write_resource::<GameTime>() = GameTime(start_time.elapsed().as_secs());
+    match state {
+      GameState::Exiting => break,
+      GameState::Restarting => {
+        let seed = match replay.as_ref().and_then(|r| r.restart_seed(ticks)) {
+          Some(seed) => seed,
+          None => w.write_resource::<GameRng>().next_seed(),
+        };
+        if let Some(ref mut r) = recorder {
+          r.restart(ticks, seed);
+        }
+        println!("Restarting with seed={}", seed);
+        reset_world(w, seed);
+        round_start = ticks;
+        accumulator = 0.0;
+      }
+      GameState::Playing => {
+        while accumulator >= tick_delta && !is_player_dead(w) {
+          if let (Some(r), Some(c)) = (replay.as_mut(), replay_controls.as_mut()) {
+            r.apply(ticks, c);
+          }
+          if let Some(ref mut r) = recorder {
+            r.record(ticks);
+          }
+          logic_dispatcher.dispatch(w);
+          w.maintain();
+          ticks += 1;
+          accumulator -= tick_delta;
+          if w.read_resource::<Level>().completed {
+            let seed = w.write_resource::<GameRng>().next_seed();
+            next_level(w, seed);
+            println!("Starting level {} with seed={}", w.read_resource::<Level>().index + 1, seed);
+          }
+          *w.write_resource::<GameTime>() = GameTime::from_ticks(ticks - round_start, tick_delta);
+        }
+      }
+      GameState::GameOver => {
+        if replay.as_ref().is_some_and(|r| r.restart_seed(ticks).is_some()) {
+          *w.write_resource::<GameState>() = GameState::Restarting;
+        }
+        // Logic stops when the player dies, death animations keep playing
+        while accumulator >= tick_delta {
+          animation.run_now(w);
+          accumulator -= tick_delta;
+        }
+      }
+      _ => accumulator = 0.0,
+    }
+
+    draw.load_map(window.get_factory(), &mut w.write_resource::<AssetManager>(), &w.read_resource::<Terrain>());
+    *w.write_resource::<Interpolation>() = Interpolation((accumulator / tick_delta) as f32);
+    let changed = w.write_resource::<AssetManager>().changed();
+    if !changed.is_empty() {
+      reload_animations(w, &changed);
+      draw.reload(window.get_factory(), &mut w.write_resource::<AssetManager>(), &w.read_resource::<CritterAnimations>(), &changed);
+    }
+    draw.run_now(w);
+
+    device_renderer.draw(window.get_device());
+    window.swap_window();
 
-      device_renderer.draw(window.get_device());
+    if let Some(ref r) = replay {
+      if r.is_finished(ticks) {
+        println!("Replay finished");
+        break;
+      }
+    }
+
+    match window.poll_events() {
+      WindowStatus::Close => break,
+      WindowStatus::Resized => {
+        views_control.send((window.get_render_target_view(), window.get_depth_stencil_view()))
+          .expect("Draw system views update error");
+        let dimensions = window_dimensions(window);
+        if window.is_windowed() {
+          w.write_resource::<Settings>().resolution = (dimensions.window_width as u32, dimensions.window_height as u32);
+        }
+        *w.write_resource::<Dimensions>() = dimensions;
+      }
+      WindowStatus::Open => (),
+    }
 
-      window.swap_window();
+    let frame_time = frame_start.elapsed();
+    if frame_time < min_frame_time {
+      thread::sleep(min_frame_time - frame_time);
     }
+  }
 
-    if let WindowStatus::Close = window.poll_events() {
-      break;
+  if let Some(r) = recorder {
+    r.finish(ticks);
+  }
+}
+
+pub fn with_game_systems<'a, 'b>(builder: DispatcherBuilder<'a, 'b>,
+                                 camera_system: CameraControlSystem,
+                                 character_system: CharacterControlSystem,
+                                 mouse_system: MouseControlSystem,
+                                 command_system: ConsoleCommandSystem) -> DispatcherBuilder<'a, 'b> {
+  builder
+    .with(command_system, "console-commands", &[])
+    .with(WaveSpawnerSystem, "wave-spawner", &[])
+    .with(terrain::PreDrawSystem, "draw-prep-terrain", &[])
+    .with(bullet::PreDrawSystem, "draw-prep-bullet", &[])
+    .with(SpatialIndexSystem, "spatial-index", &["draw-prep-bullet"])
+    .with(character::PreDrawSystem, "draw-prep-character", &["spatial-index"])
+    .with(zombie::PreDrawSystem, "draw-prep-zombie", &["spatial-index"])
+    .with(zombie::CorpseSystem, "zombie-corpses", &["draw-prep-zombie"])
+    .with(AnimationSystem, "animation", &["draw-prep-character", "draw-prep-zombie"])
+    .with(camera_system, "terrain-system", &[])
+    .with(character_system, "character-system", &["draw-prep-character", "draw-prep-zombie"])
+    .with(mouse_system, "mouse-system", &["spatial-index"])
+    .with(CollisionSystem, "collision-system", &["mouse-system"])
+    .with(LevelSystem, "level-system", &["wave-spawner", "character-system"])
+}
+
+// Animators still playing replaced clips switch over so they keep matching the reloaded sprite sheets
+fn reload_animations(world: &World, changed: &[String]) {
+  let reloaded = world.write_resource::<CritterAnimations>().reload(&mut world.write_resource::<AssetManager>(), changed);
+  for (old, new) in reloaded {
+    for animator in (&mut world.write_storage::<Animator>()).join() {
+      animator.replace_clips(&old, &new);
     }
   }
 }
+
+pub fn is_player_dead(world: &World) -> bool {
+  world.read_storage::<character::CharacterDrawable>()
+    .join()
+    .any(|c| c.is_dead())
+}
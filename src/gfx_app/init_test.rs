@@ -0,0 +1,28 @@
+#[test]
+fn world_reset_test() {
+  use specs::{Join, world::WorldExt};
+
+  use crate::assets::AssetManager;
+  use crate::character::CharacterDrawable;
+  use crate::game::constants::{RESOLUTION_X, RESOLUTION_Y};
+  use crate::game::state::GameState;
+  use crate::gfx_app::init::{reset_world, setup_world};
+  use crate::graphics::dimensions::Dimensions;
+  use crate::zombie::waves::WaveSpawner;
+
+  let mut world = specs::World::new();
+  world.insert(AssetManager::locate(None));
+  setup_world(&mut world, Dimensions::new(RESOLUTION_X as f32, RESOLUTION_Y as f32, 1.0), 1);
+  for c in (&mut world.write_storage::<CharacterDrawable>()).join() {
+    c.stats.ammunition = 0;
+  }
+  *world.write_resource::<GameState>() = GameState::GameOver;
+
+  reset_world(&mut world, 2);
+
+  let characters = world.read_storage::<CharacterDrawable>();
+  assert_eq!(world.entities().join().count(), 1, "Reset should leave only the game entity until the first wave spawns");
+  assert_eq!(world.read_resource::<WaveSpawner>().wave, 0, "Reset should restart waves");
+  assert_eq!(characters.join().map(|c| c.stats.ammunition).collect::<Vec<_>>(), vec![10], "Reset should restore player stats");
+  assert_eq!(*world.read_resource::<GameState>(), GameState::Playing, "Reset should start a new round");
+}
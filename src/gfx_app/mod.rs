@@ -4,21 +4,29 @@ use gfx::handle::{DepthStencilView, RenderTargetView};
 use gfx::memory::Typed;
 use gfx_device_gl;
 use glutin;
-use glutin::{KeyboardInput, MouseButton, PossiblyCurrent, WindowedContext};
+use glutin::{ElementState, KeyboardInput, PossiblyCurrent, VirtualKeyCode, WindowedContext};
 use glutin::dpi::LogicalSize;
 use glutin::ElementState::{Pressed, Released};
-use glutin::VirtualKeyCode::{A, D, Escape, R, S, W, X, Z};
 use std::fmt::{Display, Formatter, Result};
+use std::path::PathBuf;
 
 use crate::character::controls::CharacterControl;
-use crate::game::constants::{GAME_TITLE, RESOLUTION_X, RESOLUTION_Y};
+use crate::game::constants::GAME_TITLE;
+use crate::game::console::ConsoleControl;
+use crate::game::settings::Settings;
+use crate::game::state::GameControl;
+use crate::gfx_app::bindings::{Action, Bindings};
 use crate::gfx_app::controls::{Control, TilemapControls};
 
+pub mod bindings;
+mod bindings_test;
 pub mod init;
+mod init_test;
 pub mod renderer;
 pub mod system;
 pub mod controls;
 pub mod mouse_controls;
+pub mod replay;
 
 pub type ColorFormat = gfx::format::Rgba8;
 pub type DepthFormat = gfx::format::DepthStencil;
@@ -26,21 +34,31 @@ pub type DepthFormat = gfx::format::DepthStencil;
 pub const COLOR_FORMAT_VALUE: SurfaceType = SurfaceType::R8_G8_B8_A8;
 pub const DEPTH_FORMAT_VALUE: SurfaceType = SurfaceType::D24_S8;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct GameOptions {
-  windowed_mode: bool,
+  pub settings: Settings,
+  pub settings_path: PathBuf,
+  pub seed: u64,
+  pub record: Option<String>,
+  pub bindings: String,
+  pub assets: Option<String>,
 }
 
 impl Display for GameOptions {
   fn fmt(&self, f: &mut Formatter) -> Result {
-    write!(f, "{}", format!("windowed_mode={}", self.windowed_mode))
+    write!(f, "{}", format!("{}, seed={}", self.settings, self.seed))
   }
 }
 
 impl GameOptions {
-  pub fn new(windowed_mode: bool) -> GameOptions {
+  pub fn new(settings: Settings, settings_path: PathBuf, seed: u64, record: Option<String>, bindings: String, assets: Option<String>) -> GameOptions {
     GameOptions {
-      windowed_mode,
+      settings,
+      settings_path,
+      seed,
+      record,
+      bindings,
+      assets,
     }
   }
 }
@@ -54,6 +72,10 @@ pub struct WindowContext {
   render_target_view: RenderTargetView<gfx_device_gl::Resources, ColorFormat>,
   depth_stencil_view: DepthStencilView<gfx_device_gl::Resources, DepthFormat>,
   mouse_pos: (f64, f64),
+  is_resized: bool,
+  is_console_open: bool,
+  is_console_key_typed: bool,
+  bindings: Bindings,
   game_options: GameOptions
 }
 
@@ -66,15 +88,17 @@ impl WindowContext {
 
     println!("{}", game_options);
 
-    let builder = if game_options.windowed_mode {
-      let logical_size = LogicalSize::new(RESOLUTION_X.into(), RESOLUTION_Y.into());
+    let settings = &game_options.settings;
+
+    let builder = if settings.windowed_mode {
+      let logical_size = LogicalSize::new(settings.resolution.0.into(), settings.resolution.1.into());
       window_title
         .with_dimensions(logical_size)
         .with_decorations(false)
     } else {
-      let monitor = {
-        events_loop.get_available_monitors().nth(0).expect("No monitor found")
-      };
+      let monitor = events_loop.get_available_monitors()
+        .nth(settings.monitor)
+        .unwrap_or_else(|| events_loop.get_primary_monitor());
       let monitor_resolution = monitor.get_dimensions();
 
       let resolution = ((monitor_resolution.width as f32 * 16.0 / 9.0) as u32, monitor_resolution.height);
@@ -86,7 +110,7 @@ impl WindowContext {
     };
 
     let window_context = glutin::ContextBuilder::new()
-      .with_vsync(true)
+      .with_vsync(settings.vsync)
       .with_double_buffer(Some(true))
       .with_pixel_format(24, 8)
       .with_srgb(true)
@@ -99,25 +123,10 @@ impl WindowContext {
         .expect("Window focus failed")
     };
 
-    let (width, height) = {
-      let inner_size = window_context.window().get_inner_size().expect("get_inner_size failed");
-      let size = inner_size.to_physical(window_context.window().get_hidpi_factor());
-      (size.width as _, size.height as _)
-    };
-
-    let aa = window_context
-      .get_pixel_format().multisampling
-      .unwrap_or(0) as u8;
-
-    let window_dimensions = (width, height, 1, aa.into());
-
     let (device, factory) = gfx_device_gl::create(|s|
       window_context.get_proc_address(s) as *const std::os::raw::c_void);
 
-    let (rtv, dsv) =
-      gfx_device_gl::create_main_targets_raw(window_dimensions,
-                                             COLOR_FORMAT_VALUE,
-                                             DEPTH_FORMAT_VALUE);
+    let (rtv, dsv) = create_main_targets(&window_context);
 
     WindowContext {
       window_context,
@@ -125,17 +134,55 @@ impl WindowContext {
       events_loop,
       device,
       factory,
-      render_target_view: RenderTargetView::new(rtv),
-      depth_stencil_view: DepthStencilView::new(dsv),
+      render_target_view: rtv,
+      depth_stencil_view: dsv,
       mouse_pos: (0.0, 0.0),
+      is_resized: false,
+      is_console_open: false,
+      is_console_key_typed: false,
+      bindings: Bindings::load(&game_options.bindings),
       game_options,
     }
   }
+
+  fn update_views(&mut self) {
+    let physical_size = self.window_context.window()
+      .get_inner_size()
+      .expect("get_inner_size failed")
+      .to_physical(self.window_context.window().get_hidpi_factor());
+    self.window_context.resize(physical_size);
+
+    let (rtv, dsv) = create_main_targets(&self.window_context);
+    self.render_target_view = rtv;
+    self.depth_stencil_view = dsv;
+  }
+}
+
+fn create_main_targets(window_context: &WindowedContext<PossiblyCurrent>)
+                       -> (RenderTargetView<gfx_device_gl::Resources, ColorFormat>, DepthStencilView<gfx_device_gl::Resources, DepthFormat>) {
+  let (width, height) = {
+    let inner_size = window_context.window().get_inner_size().expect("get_inner_size failed");
+    let size = inner_size.to_physical(window_context.window().get_hidpi_factor());
+    (size.width as _, size.height as _)
+  };
+
+  let aa = window_context
+    .get_pixel_format().multisampling
+    .unwrap_or(0) as u8;
+
+  let window_dimensions = (width, height, 1, aa.into());
+
+  let (rtv, dsv) =
+    gfx_device_gl::create_main_targets_raw(window_dimensions,
+                                           COLOR_FORMAT_VALUE,
+                                           DEPTH_FORMAT_VALUE);
+  (RenderTargetView::new(rtv), DepthStencilView::new(dsv))
 }
 
 #[derive(PartialEq, Eq)]
 pub enum WindowStatus {
   Open,
+  Resized,
   Close,
 }
 
@@ -151,6 +198,7 @@ pub trait Window<D: gfx::Device, F: gfx::Factory<D::Resources>> {
   fn get_depth_stencil_view(&mut self) -> DepthStencilView<D::Resources, DepthFormat>;
   fn poll_events(&mut self) -> WindowStatus;
   fn is_windowed(&self) -> bool;
+  fn get_bindings(&self) -> &Bindings;
 }
 
 impl Window<gfx_device_gl::Device, gfx_device_gl::Factory> for WindowContext {
@@ -175,13 +223,8 @@ impl Window<gfx_device_gl::Device, gfx_device_gl::Factory> for WindowContext {
   }
 
   fn get_viewport_size(&mut self) -> (f32, f32) {
-    if self.game_options.windowed_mode {
-      (RESOLUTION_X as f32, RESOLUTION_Y as f32)
-    } else {
-      let monitor = self.events_loop.get_available_monitors().nth(0).expect("No monitor found");
-      let monitor_resolution = monitor.get_dimensions();
-      (monitor_resolution.width as f32, monitor_resolution.height as f32)
-    }
+    let inner_size = self.window_context.window().get_inner_size().expect("get_inner_size failed");
+    (inner_size.width as f32, inner_size.height as f32)
   }
 
   fn get_device(&mut self) -> &mut gfx_device_gl::Device {
@@ -193,11 +236,7 @@ impl Window<gfx_device_gl::Device, gfx_device_gl::Factory> for WindowContext {
   }
 
   fn get_hidpi_factor(&mut self) -> f32 {
-    if self.game_options.windowed_mode {
-      1.0
-    } else {
-      self.window_context.window().get_hidpi_factor() as f32
-    }
+    self.window_context.window().get_hidpi_factor() as f32
   }
 
   fn get_render_target_view(&mut self) -> RenderTargetView<gfx_device_gl::Resources, ColorFormat> {
@@ -209,32 +248,72 @@ impl Window<gfx_device_gl::Device, gfx_device_gl::Factory> for WindowContext {
   }
 
   fn poll_events(&mut self) -> WindowStatus {
-    use glutin::WindowEvent::{CursorMoved, CloseRequested, MouseInput};
+    use glutin::WindowEvent::{CursorMoved, CloseRequested, HiDpiFactorChanged, MouseInput, ReceivedCharacter, Resized};
 
-    let controls = match self.controls {
-      Some(ref mut c) => c,
-      None => panic!("Terrain controls have not been initialized"),
-    };
+    let mut controls = self.controls.as_mut();
+    let bindings = &self.bindings;
 
     let m_pos = &mut self.mouse_pos;
+    let is_resized = &mut self.is_resized;
+    let is_console_open = &mut self.is_console_open;
+    let is_console_key_typed = &mut self.is_console_key_typed;
     let mut game_status = WindowStatus::Open;
 
     self.events_loop.poll_events(|event| {
       game_status = if let glutin::Event::WindowEvent { event, .. } = event {
         match event {
-          glutin::WindowEvent::KeyboardInput { input, .. } => { process_keyboard_input(input, controls) }
-          MouseInput { state: Pressed, button: MouseButton::Left, .. } => {
-            controls.mouse_left_click(Some(*m_pos));
-            WindowStatus::Open
+          glutin::WindowEvent::KeyboardInput { input: KeyboardInput { state, virtual_keycode: Some(key), .. }, .. } => {
+            let action = bindings.key_action(key);
+            if state == Pressed {
+              // The console key may also type a character, which follows its key press
+              *is_console_key_typed = action == Some(Action::Console);
+            }
+            match (action, controls.as_mut()) {
+              (Some(Action::Console), Some(c)) => {
+                if state == Pressed {
+                  *is_console_open = !*is_console_open;
+                  c.console(ConsoleControl::Toggle);
+                }
+                WindowStatus::Open
+              }
+              // Releases still go through so a held key does not stay pressed
+              (Some(action), Some(c)) if *is_console_open && state == Released => process_action(action, state, *m_pos, c),
+              (_, Some(c)) if *is_console_open => {
+                if state == Pressed {
+                  *is_console_open = process_console_key(key, c);
+                }
+                WindowStatus::Open
+              }
+              (Some(action), Some(c)) => process_action(action, state, *m_pos, c),
+              (Some(Action::Pause), None) => WindowStatus::Close,
+              _ => WindowStatus::Open,
+            }
+          }
+          MouseInput { state, button, .. } => {
+            match (bindings.mouse_action(button), controls.as_mut()) {
+              // Releases still go through so a held button does not stay pressed
+              (Some(_), Some(_)) if *is_console_open && state == Pressed => WindowStatus::Open,
+              (Some(action), Some(c)) => process_action(action, state, *m_pos, c),
+              _ => WindowStatus::Open,
+            }
           }
-          MouseInput { state: Released, button: MouseButton::Left, .. } => {
-            controls.mouse_left_click(None);
+          ReceivedCharacter(ch) => {
+            if let (true, Some(c)) = (*is_console_open, controls.as_mut()) {
+              if !ch.is_control() && !*is_console_key_typed {
+                c.console(ConsoleControl::Input(ch));
+              }
+            }
+            *is_console_key_typed = false;
             WindowStatus::Open
           }
           CursorMoved { position, .. } => {
             *m_pos = ((position.x as f32).into(), (position.y as f32).into());
             WindowStatus::Open
           }
+          Resized(_) | HiDpiFactorChanged(_) => {
+            *is_resized = true;
+            WindowStatus::Open
+          }
           CloseRequested => WindowStatus::Close,
           _ => WindowStatus::Open,
         }
@@ -242,67 +321,60 @@ impl Window<gfx_device_gl::Device, gfx_device_gl::Factory> for WindowContext {
         WindowStatus::Open
       };
     });
-    game_status
+
+    if game_status == WindowStatus::Open && self.is_resized {
+      self.is_resized = false;
+      self.update_views();
+      WindowStatus::Resized
+    } else {
+      game_status
+    }
   }
 
   fn is_windowed(&self) -> bool {
-    self.game_options.windowed_mode
+    self.game_options.settings.windowed_mode
+  }
+
+  fn get_bindings(&self) -> &Bindings {
+    &self.bindings
   }
 }
 
-fn process_keyboard_input(input: glutin::KeyboardInput, controls: &mut TilemapControls) -> WindowStatus {
-  match input {
-    KeyboardInput { state: Pressed, virtual_keycode: Some(Z), .. } => {
-      controls.zoom(&Control::Negative);
-    }
-    KeyboardInput { state: Pressed, virtual_keycode: Some(X), .. } => {
-      controls.zoom(&Control::Plus);
-    }
-    KeyboardInput { state: Released, virtual_keycode: Some(Z), .. } |
-    KeyboardInput { state: Released, virtual_keycode: Some(X), .. } => {
-      controls.zoom(&Control::Released);
-    }
-    KeyboardInput { state: Pressed, virtual_keycode: Some(W), .. } => {
-      controls.move_character(CharacterControl::Up);
-    }
-    KeyboardInput { state: Pressed, virtual_keycode: Some(S), .. } => {
-      controls.move_character(CharacterControl::Down);
-    }
-    KeyboardInput { state: Released, virtual_keycode: Some(W), .. } |
-    KeyboardInput { state: Released, virtual_keycode: Some(S), .. } => {
-      controls.move_character(CharacterControl::YMoveStop);
+fn process_console_key(key: VirtualKeyCode, controls: &mut TilemapControls) -> bool {
+  match key {
+    VirtualKeyCode::Return => controls.console(ConsoleControl::Submit),
+    VirtualKeyCode::Back => controls.console(ConsoleControl::Backspace),
+    VirtualKeyCode::Escape => {
+      controls.console(ConsoleControl::Toggle);
+      return false;
     }
-    KeyboardInput { state: Pressed, virtual_keycode: Some(A), .. } => {
-      controls.move_character(CharacterControl::Left);
-    }
-    KeyboardInput { state: Pressed, virtual_keycode: Some(D), .. } => {
-      controls.move_character(CharacterControl::Right);
-    }
-    KeyboardInput { state: Released, virtual_keycode: Some(A), .. } |
-    KeyboardInput { state: Released, virtual_keycode: Some(D), .. } => {
-      controls.move_character(CharacterControl::XMoveStop);
-    }
-    KeyboardInput { state: Pressed, virtual_keycode: Some(R), .. } => {
-      controls.reload_weapon(true);
-    }
-    KeyboardInput { state: Released, virtual_keycode: Some(R), .. } => {
-      controls.reload_weapon(false);
-    }
-    KeyboardInput { state: Pressed, modifiers, .. } => {
-      if modifiers.ctrl {
-        controls.ctrl_pressed(true);
-      }
-    }
-    KeyboardInput { state: Released, modifiers, .. } => {
-      if !modifiers.ctrl {
-        controls.ctrl_pressed(false);
-      }
-    }
-  }
-  if let Some(Escape) = input.virtual_keycode {
-    WindowStatus::Close
-  } else {
-    WindowStatus::Open
+    _ => (),
   }
+  true
 }
 
+fn process_action(action: Action,
+                  state: ElementState,
+                  mouse_pos: (f64, f64),
+                  controls: &mut TilemapControls) -> WindowStatus {
+  match (action, state) {
+    (Action::ZoomIn, Pressed) => controls.zoom(&Control::Negative),
+    (Action::ZoomOut, Pressed) => controls.zoom(&Control::Plus),
+    (Action::ZoomIn, Released) | (Action::ZoomOut, Released) => controls.zoom(&Control::Released),
+    (Action::MoveUp, Pressed) => controls.move_character(CharacterControl::Up),
+    (Action::MoveDown, Pressed) => controls.move_character(CharacterControl::Down),
+    (Action::MoveUp, Released) | (Action::MoveDown, Released) => controls.move_character(CharacterControl::YMoveStop),
+    (Action::MoveLeft, Pressed) => controls.move_character(CharacterControl::Left),
+    (Action::MoveRight, Pressed) => controls.move_character(CharacterControl::Right),
+    (Action::MoveLeft, Released) | (Action::MoveRight, Released) => controls.move_character(CharacterControl::XMoveStop),
+    (Action::Reload, state) => controls.reload_weapon(state == Pressed),
+    (Action::Aim, state) => controls.ctrl_pressed(state == Pressed),
+    (Action::Fire, Pressed) => controls.mouse_left_click(Some(mouse_pos)),
+    (Action::Fire, Released) => controls.mouse_left_click(None),
+    (Action::Pause, Pressed) => controls.game_state(GameControl::Back),
+    (Action::Confirm, Pressed) => controls.game_state(GameControl::Confirm),
+    (Action::Quit, Pressed) => controls.game_state(GameControl::Quit),
+    _ => (),
+  }
+  WindowStatus::Open
+}
@@ -5,8 +5,7 @@ use specs::prelude::{Read, ReadStorage, WriteStorage};
 
 use crate::bullet::bullets::Bullets;
 use crate::character::{CharacterDrawable, controls::CharacterInputState};
-use crate::game::constants::SMALL_HILLS;
-use crate::graphics::{camera::CameraInputState, check_terrain_elevation, dimensions::Dimensions, direction};
+use crate::graphics::{check_terrain_elevation, dimensions::Dimensions, direction, spatial_grid::SpatialIndex};
 use crate::shaders::Position;
 
 type MouseEvent = channel::Sender<(MouseControl, Option<(f64, f64)>)>;
@@ -58,18 +57,18 @@ impl MouseControlSystem {
 impl<'a> specs::prelude::System<'a> for MouseControlSystem {
   type SystemData = (WriteStorage<'a, MouseInputState>,
                      WriteStorage<'a, CharacterDrawable>,
-                     ReadStorage<'a, CameraInputState>,
                      ReadStorage<'a, CharacterInputState>,
                      WriteStorage<'a, Bullets>,
+                     Read<'a, SpatialIndex>,
                      Read<'a, Dimensions>);
 
-  fn run(&mut self, (mut mouse_input, mut character_drawable, camera, character_input, mut bullets, dim): Self::SystemData) {
+  fn run(&mut self, (mut mouse_input, mut character_drawable, character_input, mut bullets, index, dim): Self::SystemData) {
     use specs::join::Join;
 
     while let Ok((control_value, value)) = self.queue.try_recv() {
       match control_value {
         MouseControl::LeftClick => {
-          for (mut mi, cd, bs, ca, ci) in (&mut mouse_input, &mut character_drawable, &mut bullets, &camera, &character_input).join() {
+          for (mut mi, cd, bs, ci) in (&mut mouse_input, &mut character_drawable, &mut bullets, &character_input).join() {
             if let Some(val) = value {
               if ci.is_shooting && cd.stats.ammunition > 0 {
                 cd.stats.ammunition -= 1;
@@ -77,8 +76,8 @@ impl<'a> specs::prelude::System<'a> for MouseControlSystem {
                 let end_point = Point2::new(val.0 as f32 * dim.hidpi_factor, val.1 as f32 * dim.hidpi_factor);
                 mi.left_click_point = Some(end_point);
                 let dir = direction(start_point, end_point);
-                let elevated_pos_y = check_terrain_elevation(ci.movement, &SMALL_HILLS);
-                Bullets::add_bullet(bs, Position::new(-ca.movement.x(), ca.movement.y() + elevated_pos_y), dir);
+                let elevated_pos_y = check_terrain_elevation(ci.movement, &index.hills);
+                Bullets::add_bullet(bs, ci.movement - Position::new(0.0, elevated_pos_y), dir);
               }
             } else {
               mi.left_click_point = None;
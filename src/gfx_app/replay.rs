@@ -0,0 +1,245 @@
+use std::fmt::{Display, Formatter, Result};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+use crossbeam_channel as channel;
+
+use crate::character::controls::CharacterControl;
+use crate::gfx_app::controls::{Control, ControlEvent, TilemapControls};
+use crate::graphics::dimensions::Dimensions;
+
+const REPLAY_VERSION: &str = "hinterland-replay 1";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayHeader {
+  pub seed: u64,
+  pub tick_rate: f64,
+  pub viewport: (f32, f32, f32),
+}
+
+impl ReplayHeader {
+  pub fn new(seed: u64, tick_rate: f64, dimensions: &Dimensions) -> ReplayHeader {
+    ReplayHeader {
+      seed,
+      tick_rate,
+      viewport: (dimensions.window_width, dimensions.window_height, dimensions.hidpi_factor),
+    }
+  }
+
+  pub fn dimensions(&self) -> Dimensions {
+    Dimensions::new(self.viewport.0, self.viewport.1, self.viewport.2)
+  }
+}
+
+impl Display for ReplayHeader {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    writeln!(f, "{}", REPLAY_VERSION)?;
+    writeln!(f, "seed {}", self.seed)?;
+    writeln!(f, "tick_rate {}", self.tick_rate)?;
+    write!(f, "viewport {} {} {}", self.viewport.0, self.viewport.1, self.viewport.2)
+  }
+}
+
+impl Display for ControlEvent {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    match self {
+      ControlEvent::Zoom(Control::Plus) => write!(f, "zoom in"),
+      ControlEvent::Zoom(Control::Negative) => write!(f, "zoom out"),
+      ControlEvent::Zoom(Control::Released) => write!(f, "zoom stop"),
+      ControlEvent::Character(control) => write!(f, "character {}", match control {
+        CharacterControl::Left => "left",
+        CharacterControl::Right => "right",
+        CharacterControl::Up => "up",
+        CharacterControl::Down => "down",
+        CharacterControl::XMoveStop => "x_stop",
+        CharacterControl::YMoveStop => "y_stop",
+        CharacterControl::CtrlPressed => "ctrl_pressed",
+        CharacterControl::CtrlReleased => "ctrl_released",
+        CharacterControl::ReloadPressed => "reload_pressed",
+        CharacterControl::ReloadReleased => "reload_released",
+      }),
+      ControlEvent::MouseLeftClick(Some((x, y))) => write!(f, "click {} {}", x, y),
+      ControlEvent::MouseLeftClick(None) => write!(f, "click release"),
+      ControlEvent::Console(command) => write!(f, "console {}", command),
+    }
+  }
+}
+
+impl FromStr for ControlEvent {
+  type Err = String;
+
+  fn from_str(s: &str) -> std::result::Result<ControlEvent, String> {
+    let parts = s.split_whitespace().collect::<Vec<&str>>();
+    match parts.as_slice() {
+      ["zoom", "in"] => Ok(ControlEvent::Zoom(Control::Plus)),
+      ["zoom", "out"] => Ok(ControlEvent::Zoom(Control::Negative)),
+      ["zoom", "stop"] => Ok(ControlEvent::Zoom(Control::Released)),
+      ["character", control] => {
+        let control = match *control {
+          "left" => CharacterControl::Left,
+          "right" => CharacterControl::Right,
+          "up" => CharacterControl::Up,
+          "down" => CharacterControl::Down,
+          "x_stop" => CharacterControl::XMoveStop,
+          "y_stop" => CharacterControl::YMoveStop,
+          "ctrl_pressed" => CharacterControl::CtrlPressed,
+          "ctrl_released" => CharacterControl::CtrlReleased,
+          "reload_pressed" => CharacterControl::ReloadPressed,
+          "reload_released" => CharacterControl::ReloadReleased,
+          _ => return Err(format!("Unknown character control: {}", control)),
+        };
+        Ok(ControlEvent::Character(control))
+      }
+      ["click", "release"] => Ok(ControlEvent::MouseLeftClick(None)),
+      ["console", command @ ..] if !command.is_empty() => Ok(ControlEvent::Console(command.join(" ").parse()?)),
+      ["click", x, y] => {
+        let x = x.parse::<f64>().map_err(|e| e.to_string())?;
+        let y = y.parse::<f64>().map_err(|e| e.to_string())?;
+        Ok(ControlEvent::MouseLeftClick(Some((x, y))))
+      }
+      _ => Err(format!("Unknown control event: {}", s)),
+    }
+  }
+}
+
+pub struct Recorder {
+  writer: BufWriter<File>,
+  queue: channel::Receiver<ControlEvent>,
+}
+
+impl Recorder {
+  pub fn new(path: &str, header: &ReplayHeader) -> (Recorder, channel::Sender<ControlEvent>) {
+    let (tx, rx) = channel::unbounded();
+    let file = File::create(path).expect("Replay file creation failed");
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "{}", header).expect("Replay header write failed");
+    (Recorder {
+      writer,
+      queue: rx,
+    }, tx)
+  }
+
+  pub fn record(&mut self, tick: u64) {
+    while let Ok(event) = self.queue.try_recv() {
+      writeln!(self.writer, "{} {}", tick, event).expect("Replay event write failed");
+    }
+  }
+
+  // Restarts are recorded with their seed so the whole session replays in one file
+  pub fn restart(&mut self, tick: u64, seed: u64) {
+    self.record(tick);
+    writeln!(self.writer, "{} restart {}", tick, seed).expect("Replay restart write failed");
+  }
+
+  pub fn finish(mut self, tick: u64) {
+    self.record(tick);
+    writeln!(self.writer, "end {}", tick).expect("Replay end write failed");
+    self.writer.flush().expect("Replay file flush failed");
+  }
+}
+
+pub struct Replay {
+  pub header: ReplayHeader,
+  events: Vec<(u64, ControlEvent)>,
+  restarts: Vec<(u64, u64)>,
+  end_tick: u64,
+  cursor: usize,
+}
+
+impl Replay {
+  pub fn load(path: &str) -> Replay {
+    let content = fs::read_to_string(path).expect("Replay file read failed");
+    match content.parse() {
+      Ok(replay) => replay,
+      Err(err) => panic!("Invalid replay file {}: {}", path, err),
+    }
+  }
+
+  pub fn ticks(&self) -> u64 {
+    self.end_tick
+  }
+
+  pub fn is_finished(&self, tick: u64) -> bool {
+    tick >= self.end_tick
+  }
+
+  pub fn restart_seed(&self, tick: u64) -> Option<u64> {
+    self.restarts.iter()
+      .find(|(restart_tick, _)| *restart_tick == tick)
+      .map(|(_, seed)| *seed)
+  }
+
+  pub fn apply(&mut self, tick: u64, controls: &mut TilemapControls) {
+    while let Some((event_tick, event)) = self.events.get(self.cursor) {
+      if *event_tick > tick {
+        break;
+      }
+      controls.apply(event.clone());
+      self.cursor += 1;
+    }
+  }
+}
+
+impl FromStr for Replay {
+  type Err = String;
+
+  fn from_str(s: &str) -> std::result::Result<Replay, String> {
+    let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    if lines.next() != Some(REPLAY_VERSION) {
+      return Err(format!("Expected '{}' header", REPLAY_VERSION));
+    }
+
+    let mut header_value = |name: &str| -> std::result::Result<Vec<String>, String> {
+      let line = lines.next().ok_or_else(|| format!("Missing {} header", name))?;
+      let mut parts = line.split_whitespace();
+      if parts.next() != Some(name) {
+        return Err(format!("Expected {} header, found '{}'", name, line));
+      }
+      Ok(parts.map(String::from).collect())
+    };
+
+    let seed = header_value("seed")?.join("").parse::<u64>().map_err(|e| e.to_string())?;
+    let tick_rate = header_value("tick_rate")?.join("").parse::<f64>().map_err(|e| e.to_string())?;
+    let viewport = header_value("viewport")?
+      .iter()
+      .map(|v| v.parse::<f32>().map_err(|e| e.to_string()))
+      .collect::<std::result::Result<Vec<f32>, String>>()?;
+    if viewport.len() != 3 {
+      return Err("Viewport header needs width, height and hidpi factor".to_string());
+    }
+
+    let mut events = Vec::new();
+    let mut restarts = Vec::new();
+    let mut end_tick = None;
+    for line in lines {
+      let (tick, event) = line.split_at(line.find(' ').unwrap_or(line.len()));
+      if tick == "end" {
+        end_tick = Some(event.trim().parse::<u64>().map_err(|e| e.to_string())?);
+        break;
+      }
+      let tick = tick.parse::<u64>().map_err(|e| format!("Invalid tick '{}': {}", tick, e))?;
+      match event.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        ["restart", seed] => restarts.push((tick, seed.parse::<u64>().map_err(|e| format!("Invalid restart seed '{}': {}", seed, e))?)),
+        _ => events.push((tick, event.parse::<ControlEvent>()?)),
+      }
+    }
+
+    let last_event_tick = events.iter().map(|(tick, _)| *tick).chain(restarts.iter().map(|(tick, _)| *tick))
+      .max()
+      .map_or(0, |tick| tick + 1);
+
+    Ok(Replay {
+      header: ReplayHeader {
+        seed,
+        tick_rate,
+        viewport: (viewport[0], viewport[1], viewport[2]),
+      },
+      events,
+      restarts,
+      end_tick: end_tick.unwrap_or(last_event_tick),
+      cursor: 0,
+    })
+  }
+}
@@ -1,23 +1,31 @@
 use std::time::Instant;
 
+use crossbeam_channel as channel;
 use gfx;
 use specs;
-use specs::prelude::{Read, WriteStorage};
+use specs::prelude::{Read, ReadStorage, WriteStorage};
 
 use crate::{bullet, terrain_shape};
+use crate::assets::AssetManager;
 use crate::character;
-use crate::critter::CharacterSprite;
+use crate::critter::CritterAnimations;
+use crate::game::console::DevSettings;
 use crate::game::constants::{CURRENT_AMMO_TEXT, GAME_VERSION, HUD_TEXTS};
+use crate::game::state::GameState;
 use crate::gfx_app::{ColorFormat, DepthFormat};
 use crate::gfx_app::renderer::EncoderQueue;
-use crate::graphics::{DeltaTime, orientation::{Orientation, Stance}};
+use crate::graphics::{animation::Animator, camera::CameraInputState, dimensions::Dimensions, Interpolation, orientation::Orientation};
 use crate::graphics::Drawables;
 use crate::hud;
+use crate::shaders::Position;
 use crate::terrain;
+use crate::terrain::tile_map::Terrain;
 use crate::terrain_object;
 use crate::terrain_object::TerrainTexture;
 use crate::zombie;
 
+pub type TargetViews<R> = (gfx::handle::RenderTargetView<R, ColorFormat>, gfx::handle::DepthStencilView<R, DepthFormat>);
+
 pub struct DrawSystem<D: gfx::Device> {
   render_target_view: gfx::handle::RenderTargetView<D::Resources, ColorFormat>,
   depth_stencil_view: gfx::handle::DepthStencilView<D::Resources, DepthFormat>,
@@ -28,71 +36,102 @@ pub struct DrawSystem<D: gfx::Device> {
   terrain_object_system: [terrain_object::TerrainObjectDrawSystem<D::Resources>; 3],
   terrain_shape_system: [terrain_shape::TerrainShapeDrawSystem<D::Resources>; 9],
   text_system: [hud::TextDrawSystem<D::Resources>; 3],
+  fps_text: hud::TextDrawable,
   encoder_queue: EncoderQueue<D>,
+  views_queue: channel::Receiver<TargetViews<D::Resources>>,
   game_time: Instant,
   frames: u32,
-  cool_down: f64,
-  run_cool_down: f64,
-  fire_cool_down: f64,
 }
 
 impl<D: gfx::Device> DrawSystem<D> {
   pub fn new<F>(factory: &mut F,
+                assets: &mut AssetManager,
+                terrain: &Terrain,
                 rtv: &gfx::handle::RenderTargetView<D::Resources, ColorFormat>,
                 dsv: &gfx::handle::DepthStencilView<D::Resources, DepthFormat>,
                 encoder_queue: EncoderQueue<D>)
-                -> DrawSystem<D>
+                -> (DrawSystem<D>, channel::Sender<TargetViews<D::Resources>>)
     where F: gfx::Factory<D::Resources> {
-    DrawSystem {
+    let (tx, rx) = channel::unbounded();
+    (DrawSystem {
       render_target_view: rtv.clone(),
       depth_stencil_view: dsv.clone(),
-      terrain_system: terrain::TerrainDrawSystem::new(factory, rtv.clone(), dsv.clone()),
-      character_system: character::CharacterDrawSystem::new(factory, rtv.clone(), dsv.clone()),
-      zombie_system: zombie::ZombieDrawSystem::new(factory, rtv.clone(), dsv.clone()),
-      bullet_system: bullet::BulletDrawSystem::new(factory, rtv.clone(), dsv.clone()),
+      terrain_system: terrain::TerrainDrawSystem::new(factory, assets, terrain, rtv.clone(), dsv.clone()),
+      character_system: character::CharacterDrawSystem::new(factory, assets, rtv.clone(), dsv.clone()),
+      zombie_system: zombie::ZombieDrawSystem::new(factory, assets, rtv.clone(), dsv.clone()),
+      bullet_system: bullet::BulletDrawSystem::new(factory, assets, rtv.clone(), dsv.clone()),
       terrain_object_system: [
-        terrain_object::TerrainObjectDrawSystem::new(factory, rtv.clone(), dsv.clone(), TerrainTexture::Ammo),
-        terrain_object::TerrainObjectDrawSystem::new(factory, rtv.clone(), dsv.clone(), TerrainTexture::House),
-        terrain_object::TerrainObjectDrawSystem::new(factory, rtv.clone(), dsv.clone(), TerrainTexture::Tree)
+        terrain_object::TerrainObjectDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), TerrainTexture::Ammo),
+        terrain_object::TerrainObjectDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), TerrainTexture::House),
+        terrain_object::TerrainObjectDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), TerrainTexture::Tree)
       ],
       terrain_shape_system: [
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::Right),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::DownRight),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::Down),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::DownLeft),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::Left),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::UpLeft),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::UpRight),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::Normal),
-        terrain_shape::TerrainShapeDrawSystem::new(factory, rtv.clone(), dsv.clone(), Orientation::Up),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::Right),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::DownRight),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::Down),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::DownLeft),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::Left),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::UpLeft),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::UpRight),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::Normal),
+        terrain_shape::TerrainShapeDrawSystem::new(factory, assets, rtv.clone(), dsv.clone(), Orientation::Up),
       ],
       text_system: [
-        hud::TextDrawSystem::new(factory, &HUD_TEXTS, GAME_VERSION, rtv.clone(), dsv.clone()),
-        hud::TextDrawSystem::new(factory, &HUD_TEXTS, CURRENT_AMMO_TEXT, rtv.clone(), dsv.clone()),
-        hud::TextDrawSystem::new(factory, &HUD_TEXTS, CURRENT_AMMO_TEXT, rtv.clone(), dsv.clone())
+        hud::TextDrawSystem::new(factory, assets, &HUD_TEXTS, GAME_VERSION, rtv.clone(), dsv.clone()),
+        hud::TextDrawSystem::new(factory, assets, &HUD_TEXTS, CURRENT_AMMO_TEXT, rtv.clone(), dsv.clone()),
+        hud::TextDrawSystem::new(factory, assets, &HUD_TEXTS, CURRENT_AMMO_TEXT, rtv.clone(), dsv.clone())
       ],
+      fps_text: hud::TextDrawable::new("", Position::new(1.76, -0.02)),
       encoder_queue,
+      views_queue: rx,
       game_time: Instant::now(),
       frames: 0,
-      cool_down: 1.0,
-      run_cool_down: 1.0,
-      fire_cool_down: 1.0,
+    }, tx)
+  }
+
+  fn update_views(&mut self,
+                  rtv: gfx::handle::RenderTargetView<D::Resources, ColorFormat>,
+                  dsv: gfx::handle::DepthStencilView<D::Resources, DepthFormat>) {
+    self.terrain_system.update_views(rtv.clone(), dsv.clone());
+    self.character_system.update_views(rtv.clone(), dsv.clone());
+    self.zombie_system.update_views(rtv.clone(), dsv.clone());
+    self.bullet_system.update_views(rtv.clone(), dsv.clone());
+    for system in &mut self.terrain_object_system {
+      system.update_views(rtv.clone(), dsv.clone());
+    }
+    for system in &mut self.terrain_shape_system {
+      system.update_views(rtv.clone(), dsv.clone());
+    }
+    for system in &mut self.text_system {
+      system.update_views(rtv.clone(), dsv.clone());
     }
+    self.render_target_view = rtv;
+    self.depth_stencil_view = dsv;
   }
 
-  fn update_cooldowns(&mut self, delta: f64) {
-    if self.cool_down == 0.0 {
-      self.cool_down += 0.05;
+  // Rebuilds the terrain when the level switched to another map
+  pub fn load_map<F>(&mut self, factory: &mut F, assets: &mut AssetManager, terrain: &Terrain)
+    where F: gfx::Factory<D::Resources> {
+    if self.terrain_system.map() != terrain.map {
+      self.terrain_system = terrain::TerrainDrawSystem::new(factory, assets, terrain, self.render_target_view.clone(), self.depth_stencil_view.clone());
+    }
+  }
+
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, animations: &CritterAnimations, changed: &[String])
+    where F: gfx::Factory<D::Resources> {
+    self.terrain_system.reload(factory, assets, changed);
+    self.character_system.reload(factory, assets, &animations.character, changed);
+    self.zombie_system.reload(factory, assets, &animations.zombie, changed);
+    self.bullet_system.reload(factory, assets, changed);
+    for system in &mut self.terrain_object_system {
+      system.reload(factory, assets, changed);
     }
-    if self.fire_cool_down == 0.0 {
-      self.fire_cool_down += 0.2;
+    for system in &mut self.terrain_shape_system {
+      system.reload(factory, assets, changed);
     }
-    if self.run_cool_down == 0.0 {
-      self.run_cool_down += 0.02;
+    for system in &mut self.text_system {
+      system.reload(factory, assets, changed);
     }
-    self.cool_down = (self.cool_down - delta).max(0.0);
-    self.run_cool_down = (self.run_cool_down - delta).max(0.0);
-    self.fire_cool_down = (self.fire_cool_down - delta).max(0.0);
   }
 }
 
@@ -102,28 +141,38 @@ impl<'a, D> specs::prelude::System<'a> for DrawSystem<D>
   type SystemData = (WriteStorage<'a, terrain::TerrainDrawable>,
                      WriteStorage<'a, terrain_shape::terrain_shape_objects::TerrainShapeObjects>,
                      WriteStorage<'a, character::CharacterDrawable>,
-                     WriteStorage<'a, CharacterSprite>,
+                     ReadStorage<'a, Animator>,
                      WriteStorage<'a, hud::hud_objects::HudObjects>,
-                     WriteStorage<'a, zombie::zombies::Zombies>,
+                     ReadStorage<'a, CameraInputState>,
+                     ReadStorage<'a, zombie::Renderable>,
+                     ReadStorage<'a, zombie::SpriteAnimation>,
                      WriteStorage<'a, bullet::bullets::Bullets>,
                      WriteStorage<'a, terrain_object::terrain_objects::TerrainObjects>,
-                     Read<'a, DeltaTime>);
+                     Read<'a, Interpolation>,
+                     Read<'a, Dimensions>,
+                     Read<'a, GameState>,
+                     Read<'a, DevSettings>);
 
-  fn run(&mut self, (mut terrain, mut terrain_shape, mut character, mut character_sprite, mut hud_objects, mut zombies, mut bullets, mut terrain_objects, dt): Self::SystemData) {
+  fn run(&mut self, (mut terrain, mut terrain_shape, mut character, animators, mut hud_objects, camera_input, zombie_renderables, zombie_animations, mut bullets, mut terrain_objects, interpolation, dim, state, dev): Self::SystemData) {
     use specs::join::Join;
+
+    while let Ok((rtv, dsv)) = self.views_queue.try_recv() {
+      self.update_views(rtv, dsv);
+    }
+
     let mut encoder = self.encoder_queue.receiver
       .recv()
       .expect("Encoder error");
 
-    self.update_cooldowns(dt.0);
-
     let current_time = Instant::now();
+
+    let alpha = interpolation.0;
     self.frames += 1;
 
     let time_passed = current_time.duration_since(self.game_time).as_secs();
 
-    if cfg!(feature = "framerate") && time_passed >= 1 {
-      println!("{:?} ms/frames", 1000.0 / f64::from(self.frames));
+    if dev.show_fps && time_passed >= 1 {
+      self.fps_text.update(format!("{} FPS {:.1} ms", self.frames, 1000.0 / f64::from(self.frames)));
       self.frames = 0;
       self.game_time = Instant::now();
     }
@@ -131,43 +180,40 @@ impl<'a, D> specs::prelude::System<'a> for DrawSystem<D>
     encoder.clear(&self.render_target_view, [16.0 / 256.0, 16.0 / 256.0, 20.0 / 256.0, 1.0]);
     encoder.clear_depth(&self.depth_stencil_view, 1.0);
 
-    for (t, t_shape, c, cs, hds, zs, bs, obj) in (&mut terrain, &mut terrain_shape, &mut character, &mut character_sprite, &mut hud_objects,
-                                         &mut zombies, &mut bullets, &mut terrain_objects).join() {
-      self.terrain_system.draw(t, time_passed,  &mut encoder);
+    for (t, t_shape, c, animator, hds, camera, bs, obj) in (&mut terrain, &mut terrain_shape, &mut character, &animators, &mut hud_objects,
+                                                 &camera_input, &mut bullets, &mut terrain_objects).join() {
+      let projection = dim.world_to_projection(camera, alpha);
+
+      self.terrain_system.draw(t, &projection, time_passed, &mut encoder);
 
       for hud in &mut hds.objects {
         self.text_system[0].draw(hud, &mut encoder);
         self.text_system[1].draw(hud, &mut encoder);
       }
 
-      if self.cool_down == 0.0 {
-        if c.stance == Stance::Walking {
-          cs.update_run();
-        }
-        for z in &mut zs.zombies {
-          match z.stance {
-            Stance::NormalDeath => z.update_death_idx(5),
-            Stance::CriticalDeath => z.update_death_idx(7),
-            Stance::Walking => z.update_alive_idx(7),
-            Stance::Still => z.update_alive_idx(3),
-            _ => ()
-          };
+      if *state != GameState::Playing {
+        for menu in &hds.menu {
+          self.text_system[2].draw(menu, &mut encoder);
         }
-      } else if self.fire_cool_down == 0.0 && c.stance == Stance::Firing {
-        cs.update_fire();
       }
 
-      if self.run_cool_down == 0.0 {
-        for z in &mut zs.zombies {
-          if let Stance::Running = z.stance {
-            z.update_alive_idx(7)
-          }
-        }
+      for line in &hds.console {
+        self.text_system[2].draw(line, &mut encoder);
+      }
+
+      if dev.show_fps {
+        self.text_system[2].draw(&self.fps_text, &mut encoder);
       }
 
       let mut drawables: Vec<Drawables> = vec![];
       drawables.append(&mut bs.bullets.iter().map(|b| Drawables::Bullet(b)).collect());
-      drawables.append(&mut zs.zombies.iter_mut().map(|z| Drawables::Zombie(z)).collect());
+      drawables.append(&mut (&zombie_renderables, &zombie_animations, &animators).join()
+        .map(|(renderable, animation, animator)| Drawables::Zombie(zombie::ZombieDrawable {
+          renderable,
+          animation,
+          animator,
+        }))
+        .collect());
 
       for o in &obj.objects {
         match o.object_type {
@@ -180,33 +226,33 @@ impl<'a, D> specs::prelude::System<'a> for DrawSystem<D>
       drawables.push(Drawables::Character(c));
 
       drawables.sort_by(|a, b| {
-        Drawables::get_vertical_pos(b)
-          .partial_cmp(&Drawables::get_vertical_pos(a))
+        Drawables::get_vertical_pos(a)
+          .partial_cmp(&Drawables::get_vertical_pos(b))
           .expect("Z-axis sorting failed")
       });
 
       for ts in &t_shape.objects {
         match ts.get_shape() {
-          Orientation::Right => self.terrain_shape_system[0].draw(ts, time_passed, &mut encoder),
-          Orientation::DownRight => self.terrain_shape_system[1].draw(ts, time_passed, &mut encoder),
-          Orientation::Down => self.terrain_shape_system[2].draw(ts, time_passed, &mut encoder),
-          Orientation::DownLeft => self.terrain_shape_system[3].draw(ts, time_passed, &mut encoder),
-          Orientation::Left => self.terrain_shape_system[4].draw(ts, time_passed, &mut encoder),
-          Orientation::UpLeft => self.terrain_shape_system[5].draw(ts, time_passed, &mut encoder),
-          Orientation::UpRight => self.terrain_shape_system[6].draw(ts, time_passed, &mut encoder),
-          Orientation::Normal => self.terrain_shape_system[7].draw(ts, time_passed, &mut encoder),
-          Orientation::Up => self.terrain_shape_system[8].draw(ts, time_passed, &mut encoder),
+          Orientation::Right => self.terrain_shape_system[0].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::DownRight => self.terrain_shape_system[1].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::Down => self.terrain_shape_system[2].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::DownLeft => self.terrain_shape_system[3].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::Left => self.terrain_shape_system[4].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::UpLeft => self.terrain_shape_system[5].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::UpRight => self.terrain_shape_system[6].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::Normal => self.terrain_shape_system[7].draw(ts, &projection, time_passed, &mut encoder),
+          Orientation::Up => self.terrain_shape_system[8].draw(ts, &projection, time_passed, &mut encoder),
         }
       }
 
       for e in &mut drawables {
         match *e {
-          Drawables::Bullet(ref e) => { self.bullet_system.draw(e, &mut encoder) }
-          Drawables::Zombie(ref mut e) => { self.zombie_system.draw(e, &mut encoder) }
-          Drawables::TerrainAmmo(ref mut e) => { self.terrain_object_system[0].draw(e, time_passed, &mut encoder) }
-          Drawables::TerrainHouse(ref mut e) => { self.terrain_object_system[1].draw(e, time_passed, &mut encoder) }
-          Drawables::TerrainTree(ref mut e) => { self.terrain_object_system[2].draw(e, time_passed, &mut encoder) }
-          Drawables::Character(ref mut e) => { self.character_system.draw(e, cs, &mut encoder) }
+          Drawables::Bullet(ref e) => { self.bullet_system.draw(e, &projection, alpha, &mut encoder) }
+          Drawables::Zombie(ref e) => { self.zombie_system.draw(e, &projection, alpha, &mut encoder) }
+          Drawables::TerrainAmmo(ref mut e) => { self.terrain_object_system[0].draw(e, &projection, time_passed, &mut encoder) }
+          Drawables::TerrainHouse(ref mut e) => { self.terrain_object_system[1].draw(e, &projection, time_passed, &mut encoder) }
+          Drawables::TerrainTree(ref mut e) => { self.terrain_object_system[2].draw(e, &projection, time_passed, &mut encoder) }
+          Drawables::Character(ref mut e) => { self.character_system.draw(e, &projection, animator, alpha, &mut encoder) }
         }
       }
     }
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use specs;
+use specs::prelude::{Read, WriteStorage};
+
+use crate::graphics::DeltaTime;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationClip {
+  pub sprites: String,
+  pub frames: usize,
+  pub fps: f32,
+  pub looping: bool,
+  pub events: Vec<(usize, String)>,
+}
+
+impl AnimationClip {
+  pub fn new(sprites: &str, frames: usize, fps: f32, looping: bool) -> AnimationClip {
+    AnimationClip {
+      sprites: sprites.to_string(),
+      frames,
+      fps,
+      looping,
+      events: Vec::new(),
+    }
+  }
+
+  pub fn with_event(mut self, frame: usize, event: &str) -> AnimationClip {
+    self.events.push((frame, event.to_string()));
+    self
+  }
+
+  fn frame_events(&self, frame: usize) -> impl Iterator<Item=&String> {
+    self.events.iter()
+      .filter(move |(f, _)| *f == frame)
+      .map(|(_, event)| event)
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnimationSet {
+  clips: HashMap<String, AnimationClip>,
+}
+
+impl AnimationSet {
+  pub fn new() -> AnimationSet {
+    AnimationSet {
+      clips: HashMap::new(),
+    }
+  }
+
+  pub fn with_clip(mut self, name: &str, clip: AnimationClip) -> AnimationSet {
+    self.clips.insert(name.to_string(), clip);
+    self
+  }
+
+  pub fn clip(&self, name: &str) -> &AnimationClip {
+    match self.clips.get(name) {
+      Some(clip) => clip,
+      None => panic!("Animation clip {} not found", name),
+    }
+  }
+
+  pub fn clips(&self) -> impl Iterator<Item=(&str, &AnimationClip)> {
+    self.clips.iter().map(|(name, clip)| (name.as_str(), clip))
+  }
+
+  pub fn contains(&self, name: &str) -> bool {
+    self.clips.contains_key(name)
+  }
+}
+
+// Events hold the frame events reached during the last update
+pub struct Animator {
+  clips: Arc<AnimationSet>,
+  clip: String,
+  frame: usize,
+  elapsed: f32,
+  finished: bool,
+  started: bool,
+  events: Vec<String>,
+}
+
+impl Animator {
+  pub fn new(clips: Arc<AnimationSet>, clip: &str) -> Animator {
+    let mut animator = Animator {
+      clips,
+      clip: String::new(),
+      frame: 0,
+      elapsed: 0.0,
+      finished: false,
+      started: false,
+      events: Vec::new(),
+    };
+    animator.play(clip);
+    animator
+  }
+
+  pub fn play(&mut self, clip: &str) {
+    if self.clip == clip {
+      return;
+    }
+    self.clip = clip.to_string();
+    self.frame = 0;
+    self.elapsed = 0.0;
+    self.finished = false;
+    self.started = true;
+  }
+
+  pub fn update(&mut self, delta: f32) {
+    let clips = Arc::clone(&self.clips);
+    let clip = clips.clip(&self.clip);
+    self.events.clear();
+    if self.started {
+      self.started = false;
+      self.events.extend(clip.frame_events(0).cloned());
+    }
+    if self.finished || clip.fps <= 0.0 {
+      return;
+    }
+
+    let frame_time = 1.0 / clip.fps;
+    self.elapsed += delta;
+    while self.elapsed >= frame_time {
+      self.elapsed -= frame_time;
+      if self.frame + 1 < clip.frames {
+        self.frame += 1;
+      } else if clip.looping {
+        self.frame = 0;
+      } else {
+        self.finished = true;
+        break;
+      }
+      self.events.extend(clip.frame_events(self.frame).cloned());
+    }
+  }
+
+  pub fn clip(&self) -> &str {
+    &self.clip
+  }
+
+  pub fn sprites(&self) -> &str {
+    &self.clips.clip(&self.clip).sprites
+  }
+
+  pub fn frame(&self) -> usize {
+    self.frame
+  }
+
+  pub fn is_finished(&self) -> bool {
+    self.finished
+  }
+
+  pub fn events(&self) -> &[String] {
+    &self.events
+  }
+
+  // Moves the animator over to reloaded clips when it still plays the replaced ones
+  pub fn replace_clips(&mut self, old: &Arc<AnimationSet>, new: &Arc<AnimationSet>) {
+    if Arc::ptr_eq(&self.clips, old) {
+      self.clips = Arc::clone(new);
+    }
+  }
+}
+
+impl specs::prelude::Component for Animator {
+  type Storage = specs::storage::VecStorage<Animator>;
+}
+
+pub struct AnimationSystem;
+
+impl<'a> specs::prelude::System<'a> for AnimationSystem {
+  type SystemData = (WriteStorage<'a, Animator>,
+                     Read<'a, DeltaTime>);
+
+  fn run(&mut self, (mut animators, dt): Self::SystemData) {
+    use specs::join::Join;
+
+    let delta = dt.0 as f32;
+    for animator in (&mut animators).join() {
+      animator.update(delta);
+    }
+  }
+}
@@ -0,0 +1,49 @@
+#[test]
+fn animator_loop_test() {
+  use std::sync::Arc;
+
+  use crate::graphics::animation::{AnimationClip, AnimationSet, Animator};
+
+  let clips = AnimationSet::new()
+    .with_clip("walk", AnimationClip::new("walk", 4, 10.0, true).with_event(2, "step"))
+    .with_clip("death", AnimationClip::new("death", 3, 10.0, false).with_event(0, "fall"));
+  let mut animator = Animator::new(Arc::new(clips), "walk");
+
+  animator.update(0.25);
+  assert_eq!(animator.frame(), 2, "Two frames should pass at 10 fps");
+  assert_eq!(animator.events(), ["step"]);
+
+  animator.update(0.2);
+  assert_eq!(animator.frame(), 0, "Looping clip should wrap around");
+  assert!(animator.events().is_empty(), "Events should only hold the last update");
+
+  animator.play("walk");
+  assert_eq!(animator.frame(), 0);
+  animator.update(0.05);
+  assert_eq!(animator.frame(), 1, "Playing the current clip should not restart it");
+
+  animator.play("death");
+  animator.update(1.0);
+  assert_eq!(animator.clip(), "death");
+  assert_eq!(animator.frame(), 2, "Once clip should stop at its last frame");
+  assert!(animator.is_finished());
+  assert_eq!(animator.events(), ["fall"], "Frame events should fire when a clip starts");
+}
+
+#[test]
+fn animator_replace_clips_test() {
+  use std::sync::Arc;
+
+  use crate::graphics::animation::{AnimationClip, AnimationSet, Animator};
+
+  let old = Arc::new(AnimationSet::new().with_clip("walk", AnimationClip::new("walk", 4, 10.0, true)));
+  let new = Arc::new(AnimationSet::new().with_clip("walk", AnimationClip::new("run", 2, 10.0, true)));
+  let other = Arc::new(AnimationSet::new().with_clip("walk", AnimationClip::new("walk", 4, 10.0, true)));
+  let mut animator = Animator::new(Arc::clone(&old), "walk");
+  let mut unrelated = Animator::new(Arc::clone(&other), "walk");
+
+  animator.replace_clips(&old, &new);
+  unrelated.replace_clips(&old, &new);
+  assert_eq!(animator.sprites(), "run", "Animators on the old clips should switch over");
+  assert_eq!(unrelated.sprites(), "walk", "Animators on other clips should be kept");
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use json;
+use json::JsonValue;
+
+use crate::data::read_number;
+use crate::shaders::CharacterSheet;
+
+pub struct AtlasPage {
+  pub image: String,
+  pub size: [f32; 2],
+}
+
+// uv is the region the frame occupies on its page, trim is the trimmed sprite inside its source size,
+// both normalized. Rotated frames are stored 90 degrees clockwise on the page
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtlasFrame {
+  pub page: usize,
+  pub uv: [f32; 4],
+  pub trim: [f32; 4],
+  pub rotated: bool,
+  pub source_size: [f32; 2],
+  pub pivot: [f32; 2],
+}
+
+impl AtlasFrame {
+  pub fn sheet(&self, mesh_size: [f32; 2]) -> CharacterSheet {
+    CharacterSheet {
+      uv: self.uv,
+      trim: self.trim,
+      offset: [(0.5 - self.pivot[0]) * mesh_size[0] * 2.0, (self.pivot[1] - 0.5) * mesh_size[1] * 2.0],
+      rotated: if self.rotated { 1.0 } else { 0.0 },
+      alpha: 1.0,
+    }
+  }
+}
+
+// Reads TexturePacker JSON hash and array exports, multi-page atlases use a "textures" list of pages
+pub struct Atlas {
+  pub pages: Vec<AtlasPage>,
+  frames: HashMap<String, AtlasFrame>,
+}
+
+impl Atlas {
+  pub fn parse(content: &str) -> Result<Atlas, String> {
+    let data = json::parse(content).map_err(|e| e.to_string())?;
+    let mut atlas = Atlas {
+      pages: Vec::new(),
+      frames: HashMap::new(),
+    };
+
+    if data["textures"].is_array() {
+      for texture in data["textures"].members() {
+        atlas.add_page(texture, &texture["frames"])?;
+      }
+    } else {
+      atlas.add_page(&data["meta"], &data["frames"])?;
+    }
+    if atlas.frames.is_empty() {
+      return Err("Atlas has no frames".to_string());
+    }
+    Ok(atlas)
+  }
+
+  fn add_page(&mut self, meta: &JsonValue, frames: &JsonValue) -> Result<(), String> {
+    let page = AtlasPage {
+      image: meta["image"].as_str()
+        .ok_or_else(|| format!("Atlas page {} is missing image", self.pages.len()))?
+        .to_string(),
+      size: read_size(&meta["size"], &format!("page {}", self.pages.len()))?,
+    };
+
+    let entries: Vec<(String, &JsonValue)> =
+      if frames.is_object() {
+        frames.entries().map(|(name, frame)| (name.to_string(), frame)).collect()
+      } else if frames.is_array() {
+        frames.members()
+          .map(|frame| match frame["filename"].as_str() {
+            Some(name) => Ok((name.to_string(), frame)),
+            None => Err(format!("Frame in page {} is missing filename", self.pages.len())),
+          })
+          .collect::<Result<_, String>>()?
+      } else {
+        return Err(format!("Atlas page {} is missing frames", self.pages.len()));
+      };
+
+    for (name, frame) in entries {
+      let parsed = parse_frame(self.pages.len(), &page, &name, frame)?;
+      if self.frames.insert(name.clone(), parsed).is_some() {
+        return Err(format!("Frame {} is defined more than once", name));
+      }
+    }
+    self.pages.push(page);
+    Ok(())
+  }
+
+  pub fn frame(&self, name: &str) -> Option<&AtlasFrame> {
+    self.frames.get(name)
+  }
+
+  pub fn frame_names(&self) -> impl Iterator<Item=&String> {
+    self.frames.keys()
+  }
+}
+
+fn parse_frame(page_idx: usize, page: &AtlasPage, name: &str, frame: &JsonValue) -> Result<AtlasFrame, String> {
+  let [x, y, w, h] = read_rect(&frame["frame"], &format!("frame {} frame", name))?;
+  let rotated = read_flag(frame, "rotated", name)?;
+  let trimmed = read_flag(frame, "trimmed", name)?;
+
+  let [source_w, source_h] =
+    if trimmed || frame["sourceSize"].is_object() {
+      read_size(&frame["sourceSize"], &format!("frame {} sourceSize", name))?
+    } else {
+      [w, h]
+    };
+  let [trim_x, trim_y, trim_w, trim_h] =
+    if trimmed || frame["spriteSourceSize"].is_object() {
+      read_rect(&frame["spriteSourceSize"], &format!("frame {} spriteSourceSize", name))?
+    } else {
+      [0.0, 0.0, w, h]
+    };
+  let pivot =
+    if frame["pivot"].is_null() {
+      [0.5, 0.5]
+    } else {
+      [read_number(&frame["pivot"], "x", &format!("frame {} pivot", name))?,
+        read_number(&frame["pivot"], "y", &format!("frame {} pivot", name))?]
+    };
+
+  // Frame size is the unrotated sprite size, rotated frames take h x w on the page
+  let (page_w, page_h) = if rotated { (h, w) } else { (w, h) };
+  if x + page_w > page.size[0] || y + page_h > page.size[1] {
+    return Err(format!("Frame {} is outside of page {}", name, page_idx));
+  }
+  if source_w <= 0.0 || source_h <= 0.0 {
+    return Err(format!("Frame {} has an empty sourceSize", name));
+  }
+
+  Ok(AtlasFrame {
+    page: page_idx,
+    uv: [x / page.size[0], y / page.size[1], page_w / page.size[0], page_h / page.size[1]],
+    trim: [trim_x / source_w, trim_y / source_h, trim_w / source_w, trim_h / source_h],
+    rotated,
+    source_size: [source_w, source_h],
+    pivot,
+  })
+}
+
+fn read_flag(data: &JsonValue, key: &str, name: &str) -> Result<bool, String> {
+  if data[key].is_null() {
+    return Ok(false);
+  }
+  data[key].as_bool().ok_or_else(|| format!("Invalid {} value for frame {}: {}", key, name, data[key]))
+}
+
+fn read_rect(data: &JsonValue, name: &str) -> Result<[f32; 4], String> {
+  Ok([read_number(data, "x", name)?, read_number(data, "y", name)?, read_number(data, "w", name)?, read_number(data, "h", name)?])
+}
+
+fn read_size(data: &JsonValue, name: &str) -> Result<[f32; 2], String> {
+  let size = [read_number(data, "w", name)?, read_number(data, "h", name)?];
+  if size[0] <= 0.0 || size[1] <= 0.0 {
+    return Err(format!("Invalid size for {}: {}", name, data));
+  }
+  Ok(size)
+}
@@ -0,0 +1,61 @@
+#[test]
+fn atlas_frame_test() {
+  use crate::graphics::atlas::Atlas;
+
+  let atlas = Atlas::parse(r#"{
+    "frames": {
+      "plain": { "frame": { "x": 0, "y": 0, "w": 50, "h": 20 } },
+      "trimmed": {
+        "frame": { "x": 50, "y": 0, "w": 10, "h": 20 },
+        "rotated": false,
+        "trimmed": true,
+        "spriteSourceSize": { "x": 5, "y": 0, "w": 10, "h": 20 },
+        "sourceSize": { "w": 20, "h": 40 },
+        "pivot": { "x": 0.5, "y": 1.0 }
+      },
+      "rotated": { "frame": { "x": 60, "y": 0, "w": 20, "h": 40 }, "rotated": true, "trimmed": false }
+    },
+    "meta": { "image": "sheet.png", "size": { "w": 100, "h": 50 } }
+  }"#).expect("Atlas should parse");
+
+  assert_eq!(atlas.pages[0].image, "sheet.png");
+  assert_eq!(atlas.frame("plain").expect("plain").uv, [0.0, 0.0, 0.5, 0.4]);
+
+  let trimmed = atlas.frame("trimmed").expect("trimmed");
+  assert_eq!(trimmed.trim, [0.25, 0.0, 0.5, 0.5]);
+  assert_eq!(trimmed.source_size, [20.0, 40.0]);
+  assert_eq!(trimmed.sheet([10.0, 10.0]).offset, [0.0, 10.0], "Bottom pivot should raise the sprite");
+
+  let rotated = atlas.frame("rotated").expect("rotated");
+  assert!(rotated.rotated);
+  assert_eq!(rotated.uv, [0.6, 0.0, 0.4, 0.4], "Rotated frames should take h x w on the page");
+  assert_eq!(rotated.sheet([10.0, 10.0]).rotated, 1.0);
+}
+
+#[test]
+fn atlas_pages_test() {
+  use crate::graphics::atlas::Atlas;
+
+  let atlas = Atlas::parse(r#"{
+    "textures": [
+      { "image": "a.png", "size": { "w": 10, "h": 10 }, "frames": [{ "filename": "one", "frame": { "x": 0, "y": 0, "w": 10, "h": 10 } }] },
+      { "image": "b.png", "size": { "w": 20, "h": 20 }, "frames": [{ "filename": "two", "frame": { "x": 10, "y": 10, "w": 10, "h": 10 } }] }
+    ]
+  }"#).expect("Multi-page atlas should parse");
+
+  assert_eq!(atlas.pages.len(), 2);
+  assert_eq!(atlas.frame("two").expect("two").page, 1);
+  assert_eq!(atlas.frame("two").expect("two").uv, [0.5, 0.5, 0.5, 0.5]);
+  assert!(atlas.frame("three").is_none());
+
+  let error = |content: &str| Atlas::parse(content).err().expect("Atlas should not parse");
+  assert_eq!(error(r#"{ "frames": { "one": { "frame": { "x": 0, "y": 0, "w": 10 } } }, "meta": { "image": "a.png", "size": { "w": 10, "h": 10 } } }"#),
+             "Missing frame one frame.h");
+  assert_eq!(error(r#"{ "frames": { "one": { "frame": { "x": 0, "y": 0, "w": 10, "h": 10 }, "trimmed": true } }, "meta": { "image": "a.png", "size": { "w": 10, "h": 10 } } }"#),
+             "Missing frame one sourceSize.w");
+  assert_eq!(error(r#"{ "frames": { "one": { "frame": { "x": 5, "y": 0, "w": 10, "h": 10 } } }, "meta": { "image": "a.png", "size": { "w": 10, "h": 10 } } }"#),
+             "Frame one is outside of page 0");
+  assert_eq!(error(r#"{ "frames": {}, "meta": { "size": { "w": 10, "h": 10 } } }"#), "Atlas page 0 is missing image");
+  assert_eq!(error(r#"{ "frames": [{ "frame": {} }], "meta": { "image": "a.png", "size": { "w": 10, "h": 10 } } }"#),
+             "Frame in page 0 is missing filename");
+}
@@ -1,28 +1,47 @@
 use crossbeam_channel as channel;
 use specs;
-use specs::prelude::WriteStorage;
+use specs::prelude::{Read, WriteStorage};
 
-use crate::game::constants::VIEW_DISTANCE;
+use crate::game::constants::{TILE_SIZE, VIEW_DISTANCE};
+use crate::game::settings::Settings;
 use crate::shaders::Position;
 
 #[derive(Clone)]
 pub struct CameraInputState {
   pub distance: f32,
-  pub movement: Position,
+  pub position: Position,
+  last_position: Position,
 }
 
 impl CameraInputState {
-  pub fn new() -> CameraInputState {
+  pub fn new(distance: f32, position: Position) -> CameraInputState {
     CameraInputState {
-      distance: VIEW_DISTANCE,
-      movement: Position::origin(),
+      distance,
+      position,
+      last_position: position,
     }
   }
+
+  pub fn follow(&mut self, target: Position) {
+    self.last_position = self.position;
+    self.position = target;
+  }
+
+  pub fn interpolated_position(&self, alpha: f32) -> Position {
+    self.last_position.lerp(self.position, alpha)
+  }
+
+  // World offsets are applied in clip space, so the view spans the camera distance on both axes
+  pub fn is_in_view(&self, position: Position) -> bool {
+    let offset = position - self.position;
+    let extent = self.distance + TILE_SIZE;
+    offset.x().abs() < extent && offset.y().abs() < extent
+  }
 }
 
 impl Default for CameraInputState {
   fn default() -> CameraInputState {
-    CameraInputState::new()
+    CameraInputState::new(VIEW_DISTANCE, Position::origin())
   }
 }
 
@@ -58,8 +77,10 @@ impl CameraControlSystem {
 }
 
 impl<'a> specs::prelude::System<'a> for CameraControlSystem {
-  type SystemData = WriteStorage<'a, CameraInputState>;
-  fn run(&mut self, mut map_input: Self::SystemData) {
+  type SystemData = (WriteStorage<'a, CameraInputState>,
+                     Read<'a, Settings>);
+
+  fn run(&mut self, (mut map_input, settings): Self::SystemData) {
     use specs::join::Join;
 
     while let Ok(control) = self.queue.try_recv() {
@@ -72,7 +93,7 @@ impl<'a> specs::prelude::System<'a> for CameraControlSystem {
     }
     if let Some(zoom) = self.zoom_level {
       for m in (&mut map_input).join() {
-        if m.distance > 200.0 && zoom < 0.0 || m.distance < 600.0 && zoom > 0.0 {
+        if m.distance > settings.min_view_distance && zoom < 0.0 || m.distance < settings.max_view_distance && zoom > 0.0 {
           m.distance += zoom;
         }
       }
@@ -2,7 +2,7 @@ use cgmath;
 use cgmath::{Matrix4, Point3, Vector3};
 
 use crate::graphics::camera::CameraInputState;
-use crate::shaders::Projection;
+use crate::shaders::{Position, Projection};
 
 #[derive(Clone, Default)]
 pub struct Dimensions {
@@ -12,8 +12,7 @@ pub struct Dimensions {
 }
 
 impl Dimensions {
-  pub fn new(window_width: f32, window_height: f32, hidpi_val: f32, is_windowed: bool) -> Dimensions {
-    let hidpi_factor = if is_windowed { 1.0 } else { hidpi_val };
+  pub fn new(window_width: f32, window_height: f32, hidpi_factor: f32) -> Dimensions {
     Dimensions {
       window_width,
       window_height,
@@ -21,21 +20,29 @@ impl Dimensions {
     }
   }
 
-  pub fn world_to_projection(&self, input: &CameraInputState) -> Projection {
+  pub fn world_to_projection(&self, input: &CameraInputState, alpha: f32) -> Projection {
     let view: Matrix4<f32> = get_view_matrix(input.distance);
+    let camera = get_camera_matrix(input.interpolated_position(alpha));
     let aspect_ratio = self.window_width / self.window_height;
-    get_projection(view, aspect_ratio)
+    get_projection(view, camera, aspect_ratio)
   }
 }
 
-pub fn get_projection(view: Matrix4<f32>, aspect_ratio: f32) -> Projection {
+pub fn get_projection(view: Matrix4<f32>, camera: Matrix4<f32>, aspect_ratio: f32) -> Projection {
   Projection {
     model: view.into(),
     view: view.into(),
     proj: cgmath::perspective(cgmath::Deg(75.0f32), aspect_ratio, 0.1, 4000.0).into(),
+    camera: camera.into(),
   }
 }
 
+// World coordinates share the tile space of coords_to_tile, which grows towards the top left of the screen
+pub fn get_camera_matrix(position: Position) -> Matrix4<f32> {
+  Matrix4::from_translation(Vector3::new(position.x(), position.y(), 0.0)) *
+    Matrix4::from_nonuniform_scale(-1.0, -1.0, 1.0)
+}
+
 pub fn get_view_matrix(view: f32) -> Matrix4<f32> {
   Matrix4::look_at(
     Point3::new(0.0, 0.0, view),
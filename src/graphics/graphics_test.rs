@@ -106,19 +106,54 @@ fn direction_movement_test() {
 #[test]
 fn tile_to_coords_test() {
   use cgmath::Point2;
-  use crate::graphics::coords_to_tile;
+  use crate::graphics::MapSize;
   use crate::shaders::Position;
 
+  let size = MapSize::new(128, 128);
+
   let up = Position::new(0.0, -5385.0);
   let down = Position::new(0.0, 5385.0);
   let right = Position::new(-5995.0, 0.0);
   let left = Position::new(5995.0, 0.0);
 
-  assert_eq!(coords_to_tile(up), Point2::new(1, 1), "Up corner");
+  assert_eq!(size.coords_to_tile(up), Point2::new(1, 1), "Up corner");
+
+  assert_eq!(size.coords_to_tile(down), Point2::new(126, 126), "Down corner");
+
+  assert_eq!(size.coords_to_tile(right), Point2::new(126, 1), "Right corner");
+
+  assert_eq!(size.coords_to_tile(left), Point2::new(1, 126), "Left corner");
+}
 
-  assert_eq!(coords_to_tile(down), Point2::new(126, 126), "Down corner");
+#[test]
+fn tile_to_coords_round_trip_test() {
+  use cgmath::Point2;
+  use crate::graphics::MapSize;
+  use crate::shaders::Position;
+
+  let size = MapSize::new(128, 128);
+  assert_eq!(Point2::new(64, 64), size.coords_to_tile(size.tile_to_coords(64, 64)));
+  assert_eq!(Point2::new(10, 90), size.coords_to_tile(size.tile_to_coords(10, 90)));
+  assert_eq!(Point2::new(100, 3), size.coords_to_tile(size.tile_to_coords(100, 3)));
+
+  let large = MapSize::new(512, 512);
+  assert_eq!(Point2::new(500, 3), large.coords_to_tile(large.tile_to_coords(500, 3)));
+  assert_eq!(Position::origin(), large.map_to_coords(256.0, 256.0), "Map center should be the world origin");
+
+  let wide = MapSize::new(200, 100);
+  assert_eq!(Point2::new(190, 95), wide.coords_to_tile(wide.tile_to_coords(190, 95)));
+  assert_eq!(Position::origin(), wide.map_to_coords(100.0, 50.0), "Map center should be the world origin");
+  assert!(wide.is_map_tile(Point2::new(150, 50)) && !wide.is_map_tile(Point2::new(50, 150)));
+}
+
+#[test]
+fn camera_matrix_test() {
+  use cgmath::Vector4;
+  use crate::graphics::dimensions::get_camera_matrix;
+  use crate::shaders::Position;
 
-  assert_eq!(coords_to_tile(right), Point2::new(126, 1), "Right corner");
+  let camera = get_camera_matrix(Position::new(100.0, -50.0));
 
-  assert_eq!(coords_to_tile(left), Point2::new(1, 126), "Left corner");
+  assert_eq!(Vector4::new(0.0, 0.0, 0.0, 1.0), camera * Vector4::new(100.0, -50.0, 0.0, 1.0), "Camera position should be at the view center");
+  assert_eq!(Vector4::new(-20.0, 10.0, 0.0, 1.0), camera * Vector4::new(120.0, -60.0, 0.0, 1.0), "World offsets should be mirrored on screen");
 }
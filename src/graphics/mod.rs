@@ -2,23 +2,29 @@ use std::f32;
 
 use cgmath;
 use cgmath::{Angle, Deg, Point2};
-use num::{Num, NumCast};
 
 use crate::bullet::BulletDrawable;
 use crate::character::CharacterDrawable;
-use crate::game::{constants::{RESOLUTION_Y, TERRAIN_OBJECTS, TILE_SIZE, TILES_PCS_H, TILES_PCS_W, Y_OFFSET}, get_rand_from_range};
+use crate::game::{constants::TILE_SIZE, GameRng};
 use crate::game::constants::TILE_WIDTH;
 use crate::gfx_app::{mouse_controls::MouseInputState};
-use crate::graphics::{dimensions::Dimensions, orientation::Orientation};
+use crate::graphics::{dimensions::Dimensions, orientation::Orientation, spatial_grid::SpatialGrid};
 use crate::shaders::Position;
+use crate::terrain::collision::CollisionGrid;
 use crate::terrain_object::TerrainObjectDrawable;
 use crate::zombie::ZombieDrawable;
 
+pub mod animation;
+mod animation_test;
+pub mod atlas;
+mod atlas_test;
 pub mod camera;
 pub mod dimensions;
 mod graphics_test;
 pub mod mesh;
 pub mod orientation;
+pub mod spatial_grid;
+mod spatial_grid_test;
 pub mod texture;
 
 const Y_MODIFIER: f32 = 0.9;
@@ -29,8 +35,17 @@ pub struct DeltaTime(pub f64);
 #[derive(Default)]
 pub struct GameTime(pub u64);
 
-pub fn flip_y_axel(point: Point2<f32>) -> Point2<f32> {
-  Point2::new(point.x, RESOLUTION_Y as f32 - point.y)
+impl GameTime {
+  pub fn from_ticks(ticks: u64, tick_delta: f64) -> GameTime {
+    GameTime((ticks as f64 * tick_delta) as u64)
+  }
+}
+
+#[derive(Default)]
+pub struct Interpolation(pub f32);
+
+pub fn flip_y_axel(point: Point2<f32>, height: f32) -> Point2<f32> {
+  Point2::new(point.x, height - point.y)
 }
 
 pub fn direction(start_point: Point2<f32>, end_point: Point2<f32>) -> f32 {
@@ -66,7 +81,7 @@ pub fn orientation_to_direction(angle_in_degrees: f32) -> Orientation {
 pub fn get_orientation_from_center(mouse_input: &MouseInputState, dim: &Dimensions) -> Orientation {
   if let Some(end_point_gl) = mouse_input.left_click_point {
     let start_point = Point2::new(dim.window_width / 2.0 * dim.hidpi_factor, dim.window_height / 2.0 * dim.hidpi_factor);
-    let dir = direction(start_point, flip_y_axel(end_point_gl));
+    let dir = direction(start_point, flip_y_axel(end_point_gl, dim.window_height * dim.hidpi_factor));
     orientation_to_direction(dir)
   } else {
     Orientation::Right
@@ -80,49 +95,77 @@ pub fn overlaps(area: Position, el: Position, width: f32, height: f32) -> bool {
     area.y() + height > el.y()
 }
 
-pub fn is_within_map_borders(point: Point2<usize>) -> bool {
-  point.x < (TILES_PCS_W - 1) && point.y < (TILES_PCS_H - 1)
+pub fn can_move(size: MapSize, screen_pos: Position) -> bool {
+  size.is_within_borders(size.coords_to_tile(screen_pos))
 }
 
-pub fn can_move(screen_pos: Position) -> bool {
-  let point = coords_to_tile(screen_pos);
-  is_within_map_borders(Point2::new(point.x as usize, point.y as usize))
+pub fn can_move_to_tile(collision: &CollisionGrid, screen_pos: Position) -> bool {
+  let tile_pos = collision.size().coords_to_tile(screen_pos);
+  !collision.is_blocked(tile_pos) && collision.size().is_map_tile(tile_pos)
 }
 
-fn is_not_terrain_object<T>(pos: Point2<T>) -> bool
-  where T: NumCast + Num, i32: std::cmp::PartialEq<T> {
-  !TERRAIN_OBJECTS.iter().any(|e| (e[0] == pos.x) && (e[1] == pos.y))
+pub fn hill_position(center: Position) -> Position {
+  Position::new(center.x(), center.y() / Y_MODIFIER).tile_center(0.0, TILE_SIZE / 2.0)
 }
 
-fn is_map_tile(pos: Point2<i32>) -> bool {
-  pos.x > 0 && pos.y > 0 && pos.x < (TILES_PCS_W - 2) as i32 && pos.y < (TILES_PCS_H - 2) as i32
+pub fn check_terrain_elevation(critter_pos: Position, hills: &SpatialGrid<()>) -> f32 {
+  let hill_radius = TILE_SIZE * 2.0;
+  let nearest_hill = hills.query_radius(critter_pos, hill_radius)
+    .map(|(hill, _)| position_distance(critter_pos, *hill))
+    .fold(hill_radius, f32::min);
+
+  (nearest_hill - hill_radius).abs()
 }
 
-pub fn can_move_to_tile(screen_pos: Position) -> bool {
-  let tile_pos = coords_to_tile(screen_pos);
-  is_not_terrain_object(tile_pos) && is_map_tile(tile_pos)
+// Map dimensions in tiles, the world origin is at the center of the map
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapSize {
+  pub width: usize,
+  pub height: usize,
 }
 
-pub fn check_terrain_elevation(critter_pos: Position, objects: &[[i32; 2]]) -> f32 {
-  let offset = TILE_SIZE / 2.0;
-  let nearest_hill = objects.iter()
-    .map(|x| {
-      position_distance(
-        critter_pos,
-        Position::new(TILE_SIZE * -x[0] as f32, TILE_SIZE * -x[1] as f32).tile_center(0.0, offset)
-      )
-    })
-    .fold(100_000_000f32, |mut min, val| {
-      if val < min {
-        min = val;
-      }
-      min
-    });
+impl MapSize {
+  pub fn new(width: usize, height: usize) -> MapSize {
+    MapSize {
+      width,
+      height,
+    }
+  }
 
-  if nearest_hill < TILE_SIZE * 2.0 {
-    (nearest_hill - TILE_SIZE * 2.0).abs()
-  } else {
-    0.0
+  // Isometric offset of the map center from tile (0, 0)
+  fn offset(&self) -> Point2<f32> {
+    Point2::new(
+      (self.width as f32 - self.height as f32) / 4.0 * TILE_WIDTH,
+      (self.width + self.height) as f32 / 4.0 * TILE_WIDTH,
+    )
+  }
+
+  pub fn coords_to_tile(&self, position: Position) -> Point2<i32> {
+    let offset = self.offset();
+    let pos = Point2::new(offset.x - position.x(), position.y() / Y_MODIFIER + offset.y);
+    Point2::new(((pos.x + pos.y) / TILE_WIDTH) as i32, ((pos.y - pos.x) / TILE_WIDTH) as i32)
+  }
+
+  pub fn tile_to_coords(&self, x: i32, y: i32) -> Position {
+    let offset = self.offset();
+    let pos = Point2::new((x - y) as f32 * TILE_WIDTH / 2.0, (x + y + 1) as f32 * TILE_WIDTH / 2.0);
+    Position::new(offset.x - pos.x, (pos.y - offset.y) * Y_MODIFIER)
+  }
+
+  // Fractional tile coordinates, whole numbers are tile corners
+  pub fn map_to_coords(&self, x: f32, y: f32) -> Position {
+    let offset = self.offset();
+    let pos = Point2::new((y - x) * TILE_WIDTH / 2.0, (x + y) * TILE_WIDTH / 2.0);
+    Position::new(pos.x + offset.x, (pos.y - offset.y) * Y_MODIFIER)
+  }
+
+  pub fn is_within_borders(&self, tile: Point2<i32>) -> bool {
+    tile.x >= 0 && tile.y >= 0 && tile.x < self.width as i32 - 1 && tile.y < self.height as i32 - 1
+  }
+
+  // Characters stay off the tiles along the map edges
+  pub fn is_map_tile(&self, tile: Point2<i32>) -> bool {
+    tile.x > 0 && tile.y > 0 && tile.x < self.width as i32 - 2 && tile.y < self.height as i32 - 2
   }
 }
 
@@ -130,25 +173,20 @@ pub fn set_position(x: i32, y: i32) -> Position {
   let x_val = x as f32;
   let y_val = y as f32;
   Position::new(
-    TILE_SIZE * x_val,
-    TILE_SIZE * Y_MODIFIER * y_val,
+    -TILE_SIZE * x_val,
+    -TILE_SIZE * Y_MODIFIER * y_val,
   )
 }
 
-pub fn coords_to_tile(position: Position) -> Point2<i32> {
-  let pos = Point2::new(-position.x(), position.y() / Y_MODIFIER + Y_OFFSET);
-  Point2::new(((pos.x + pos.y) / TILE_WIDTH) as i32, ((pos.y - pos.x) / TILE_WIDTH) as i32)
-}
-
 fn round(number: f32, precision: usize) -> f32 {
   let ten: f32 = 10.0;
   let divider = ten.powf(precision as f32);
   (number * divider).round() / divider
 }
 
-pub fn get_nearest_random_tile_position(pos: Position) -> Position {
-  fn iter(pos: Position) -> Position {
-    let offset = Position::new(get_rand_from_range(-2, 2) as f32, get_rand_from_range(-2, 2) as f32);
+pub fn get_nearest_random_tile_position(collision: &CollisionGrid, pos: Position, rng: &mut GameRng) -> Position {
+  fn iter(pos: Position, rng: &mut GameRng) -> Position {
+    let offset = Position::new(rng.get_rand_from_range(-2, 2) as f32, rng.get_rand_from_range(-2, 2) as f32);
     let offset_point = Position::new(
       round(offset.x() * TILE_WIDTH - offset.y() / TILE_WIDTH * Y_MODIFIER, 3),
       round(offset.y() * TILE_WIDTH - offset.y() / TILE_WIDTH * Y_MODIFIER, 3),
@@ -156,8 +194,8 @@ pub fn get_nearest_random_tile_position(pos: Position) -> Position {
     pos + offset_point
   }
   loop {
-    let res = iter(pos);
-    if can_move_to_tile(res) {
+    let res = iter(pos, rng);
+    if can_move_to_tile(collision, res) {
       return res;
     }
   }
@@ -167,7 +205,7 @@ pub fn distance(a: f32, b: f32) -> f32 {
   (a.powf(2.0) + b.powf(2.0)).sqrt()
 }
 
-fn position_distance(a: Position, b: Position) -> f32 {
+pub fn position_distance(a: Position, b: Position) -> f32 {
   let d = a - b;
   distance(d.x(), d.y())
 }
@@ -178,14 +216,14 @@ pub enum Drawables<'b> {
   TerrainAmmo(&'b TerrainObjectDrawable),
   TerrainHouse(&'b TerrainObjectDrawable),
   TerrainTree(&'b TerrainObjectDrawable),
-  Zombie(&'b mut ZombieDrawable),
+  Zombie(ZombieDrawable<'b>),
 }
 
 impl<'b> Drawables<'b> {
   pub fn get_vertical_pos(drawable: &Drawables) -> f32 {
     match drawable {
       Drawables::Bullet(e) => e.position.y(),
-      Drawables::Zombie(e) => e.position.y(),
+      Drawables::Zombie(e) => e.renderable.position.y(),
       Drawables::TerrainAmmo(e) => e.position.y(),
       Drawables::TerrainHouse(e) => e.position.y(),
       Drawables::TerrainTree(e) => e.position.y(),
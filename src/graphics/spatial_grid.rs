@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use cgmath::Point2;
+use specs;
+use specs::prelude::{Entities, Entity, ReadStorage, Write};
+
+use crate::bullet::bullets::Bullets;
+use crate::game::constants::TILE_WIDTH;
+use crate::graphics::{hill_position, overlaps, position_distance};
+use crate::shaders::Position;
+use crate::terrain_object::{terrain_objects::TerrainObjects, TerrainTexture};
+use crate::zombie::AiState;
+
+pub struct SpatialGrid<T> {
+  cells: HashMap<Point2<i32>, Vec<(Position, T)>>,
+}
+
+impl<T> SpatialGrid<T> {
+  pub fn new() -> SpatialGrid<T> {
+    SpatialGrid {
+      cells: HashMap::new(),
+    }
+  }
+
+  pub fn clear(&mut self) {
+    for cell in self.cells.values_mut() {
+      cell.clear();
+    }
+  }
+
+  pub fn insert(&mut self, position: Position, value: T) {
+    self.cells.entry(cell(position))
+      .or_default()
+      .push((position, value));
+  }
+
+  pub fn query_aabb(&self, center: Position, half_width: f32, half_height: f32) -> impl Iterator<Item=&(Position, T)> {
+    self.cell_entries(center, half_width, half_height)
+      .filter(move |(position, _)| overlaps(center, *position, half_width, half_height))
+  }
+
+  pub fn query_radius(&self, center: Position, radius: f32) -> impl Iterator<Item=&(Position, T)> {
+    self.cell_entries(center, radius, radius)
+      .filter(move |(position, _)| position_distance(center, *position) < radius)
+  }
+
+  fn cell_entries(&self, center: Position, half_width: f32, half_height: f32) -> impl Iterator<Item=&(Position, T)> {
+    let min = cell(center - Position::new(half_width, half_height));
+    let max = cell(center + Position::new(half_width, half_height));
+
+    (min.x..=max.x)
+      .flat_map(move |x| (min.y..=max.y).map(move |y| Point2::new(x, y)))
+      .filter_map(move |tile| self.cells.get(&tile))
+      .flat_map(|cell| cell.iter())
+  }
+}
+
+// Cells are axis aligned so the grid does not depend on the map size
+fn cell(position: Position) -> Point2<i32> {
+  Point2::new((position.x() / TILE_WIDTH).floor() as i32, (position.y() / TILE_WIDTH).floor() as i32)
+}
+
+impl<T> Default for SpatialGrid<T> {
+  fn default() -> SpatialGrid<T> {
+    SpatialGrid::new()
+  }
+}
+
+pub struct SpatialIndex {
+  pub zombies: SpatialGrid<Entity>,
+  pub bullets: SpatialGrid<usize>,
+  pub pickups: SpatialGrid<usize>,
+  pub hills: SpatialGrid<()>,
+}
+
+impl SpatialIndex {
+  pub fn new(hill_centers: &[Position]) -> SpatialIndex {
+    let mut hills = SpatialGrid::new();
+    for hill in hill_centers {
+      hills.insert(hill_position(*hill), ());
+    }
+    SpatialIndex {
+      zombies: SpatialGrid::new(),
+      bullets: SpatialGrid::new(),
+      pickups: SpatialGrid::new(),
+      hills,
+    }
+  }
+}
+
+impl Default for SpatialIndex {
+  fn default() -> SpatialIndex {
+    SpatialIndex::new(&[])
+  }
+}
+
+pub struct SpatialIndexSystem;
+
+impl<'a> specs::prelude::System<'a> for SpatialIndexSystem {
+  type SystemData = (Write<'a, SpatialIndex>,
+                     Entities<'a>,
+                     ReadStorage<'a, Position>,
+                     ReadStorage<'a, AiState>,
+                     ReadStorage<'a, Bullets>,
+                     ReadStorage<'a, TerrainObjects>);
+
+  fn run(&mut self, (mut index, entities, positions, ai_states, bullets, terrain_objects): Self::SystemData) {
+    use specs::join::Join;
+
+    index.zombies.clear();
+    index.bullets.clear();
+    index.pickups.clear();
+
+    for (entity, position, ai) in (&entities, &positions, &ai_states).join() {
+      if ai.is_alive() {
+        index.zombies.insert(*position, entity);
+      }
+    }
+
+    for bs in bullets.join() {
+      for (idx, b) in bs.bullets.iter().enumerate() {
+        index.bullets.insert(b.position, idx);
+      }
+    }
+
+    for to in terrain_objects.join() {
+      for (idx, o) in to.objects.iter().enumerate() {
+        if o.object_type == TerrainTexture::Ammo {
+          index.pickups.insert(o.position, idx);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,38 @@
+#[test]
+fn spatial_grid_aabb_test() {
+  use crate::graphics::spatial_grid::SpatialGrid;
+  use crate::shaders::Position;
+
+  let mut grid = SpatialGrid::new();
+  grid.insert(Position::new(0.0, 0.0), 0);
+  grid.insert(Position::new(10.0, -25.0), 1);
+  grid.insert(Position::new(95.0, 0.0), 2);
+  grid.insert(Position::new(-300.0, 400.0), 3);
+
+  let mut found = grid.query_aabb(Position::new(5.0, 0.0), 15.0, 30.0).map(|(_, idx)| *idx).collect::<Vec<usize>>();
+  found.sort();
+  assert_eq!(vec![0, 1], found, "Only entries inside the box should be found");
+
+  let found = grid.query_aabb(Position::new(100.0, 0.0), 10.0, 10.0).map(|(_, idx)| *idx).collect::<Vec<usize>>();
+  assert_eq!(vec![2], found, "Entries in neighbouring tiles should be found");
+}
+
+#[test]
+fn spatial_grid_radius_test() {
+  use crate::graphics::spatial_grid::SpatialGrid;
+  use crate::shaders::Position;
+
+  let mut grid = SpatialGrid::new();
+  for x in -10..10 {
+    for y in -10..10 {
+      grid.insert(Position::new(x as f32 * 50.0, y as f32 * 50.0), (x, y));
+    }
+  }
+
+  let mut found = grid.query_radius(Position::new(0.0, 0.0), 60.0).map(|(_, v)| *v).collect::<Vec<(i32, i32)>>();
+  found.sort();
+  assert_eq!(vec![(-1, 0), (0, -1), (0, 0), (0, 1), (1, 0)], found);
+
+  grid.clear();
+  assert_eq!(0, grid.query_radius(Position::new(0.0, 0.0), 500.0).count(), "Cleared grid should be empty");
+}
@@ -1,13 +1,16 @@
 use std::collections::HashMap;
 use std::hash::BuildHasher;
 use std::io::Cursor;
+use std::path::Path;
 
 use cgmath::Point2;
-use gfx::{Factory, format::Rgba8, handle::ShaderResourceView, Resources, texture::{AaMode, Kind, Mipmap, Size}};
+use gfx::{Factory, format::{Float, R32, Rgba8}, handle::ShaderResourceView, memory::cast_slice, Resources, texture::{AaMode, Kind, Mipmap, Size}};
 use image;
 use rusttype::Font;
 
+use crate::assets::AssetManager;
 use crate::gfx_app::ColorFormat;
+use crate::graphics::{atlas::Atlas, MapSize};
 use crate::hud::font::draw_text;
 
 #[derive(Clone)]
@@ -26,15 +29,46 @@ impl<R> Texture<R> where R: Resources {
 }
 
 pub fn load_texture<R, F>(factory: &mut F, data: &[u8]) -> ShaderResourceView<R, [f32; 4]> where R: Resources, F: Factory<R> {
-  let img = image::load(Cursor::new(data), image::PNG).unwrap().to_rgba();
+  match try_load_texture(factory, data) {
+    Ok(texture) => texture,
+    Err(e) => panic!("{}", e),
+  }
+}
+
+pub fn try_load_texture<R, F>(factory: &mut F, data: &[u8]) -> Result<ShaderResourceView<R, [f32; 4]>, String> where R: Resources, F: Factory<R> {
+  let img = image::load(Cursor::new(data), image::PNG)
+    .map_err(|e| format!("Couldn't decode texture {:?}", e))?
+    .to_rgba();
   let (width, height) = img.dimensions();
   let kind = Kind::D2(width as Size, height as Size, AaMode::Single);
-  match factory.create_texture_immutable_u8::<Rgba8>(kind, Mipmap::Provided, &[&img]) {
-    Ok(val) => val.1,
-    Err(e) => panic!("Couldn't load texture {:?}", e)
+  factory.create_texture_immutable_u8::<Rgba8>(kind, Mipmap::Provided, &[&img])
+    .map(|val| val.1)
+    .map_err(|e| format!("Couldn't load texture {:?}", e))
+}
+
+// One float texel per map tile, rows start from the bottom of the map
+pub fn load_tile_indices<R, F>(factory: &mut F, size: MapSize, tiles: &[f32]) -> ShaderResourceView<R, f32> where R: Resources, F: Factory<R> {
+  let kind = Kind::D2(size.width as Size, size.height as Size, AaMode::Single);
+  match factory.create_texture_immutable_u8::<(R32, Float)>(kind, Mipmap::Provided, &[cast_slice(tiles)]) {
+    Ok((_, view)) => view,
+    Err(e) => panic!("Couldn't load tile indices {:?}", e),
   }
 }
 
+// Page images are resolved relative to the atlas file
+pub fn load_atlas_textures<R, F>(factory: &mut F, assets: &mut AssetManager, atlas: &Atlas, atlas_path: &str)
+                                 -> Result<Vec<ShaderResourceView<R, [f32; 4]>>, String>
+  where R: Resources, F: Factory<R> {
+  let dir = Path::new(atlas_path).parent().unwrap_or_else(|| Path::new(""));
+  atlas.pages.iter()
+    .map(|page| {
+      let name = dir.join(&page.image).to_string_lossy().into_owned();
+      let bytes = assets.try_bytes(&name)?;
+      try_load_texture(factory, &bytes).map_err(|e| format!("Atlas page {}: {}", name, e))
+    })
+    .collect()
+}
+
 pub fn load_raw_texture<R, F>(factory: &mut F, data: &[u8], size: Point2<i32>) -> ShaderResourceView<R, [f32; 4]>
   where R: Resources, F: Factory<R> {
   let kind = Kind::D2(size.x as Size, size.y as Size, AaMode::Single);
@@ -56,7 +90,7 @@ pub fn text_texture<'a, R, F, S: BuildHasher>(factory: &mut F,
   texts.iter().for_each(|text| {
     let (texture_size, texture_data) = draw_text(&font, text_texture_height, text);
     let texture = load_raw_texture(factory, &texture_data.as_slice(), texture_size);
-    texture_cache.insert((*text).to_string(), Texture::new(texture, None));
+    texture_cache.insert((*text).to_string(), Texture::new(texture, Some(texture_size)));
   });
   texture_cache
 }
@@ -1,11 +1,13 @@
 use specs;
 
-use crate::game::constants::{CURRENT_AMMO_TEXT, CURRENT_MAGAZINE_TEXT, GAME_VERSION};
+use crate::game::constants::{CURRENT_AMMO_TEXT, CURRENT_MAGAZINE_TEXT, GAME_TITLE, GAME_VERSION};
 use crate::hud::TextDrawable;
 use crate::shaders::Position;
 
 pub struct HudObjects {
   pub objects: Vec<TextDrawable>,
+  pub menu: Vec<TextDrawable>,
+  pub console: Vec<TextDrawable>,
 }
 
 impl HudObjects {
@@ -15,7 +17,12 @@ impl HudObjects {
         TextDrawable::new(GAME_VERSION, Position::origin()),
         TextDrawable::new(CURRENT_AMMO_TEXT, Position::new(1.9, -1.9)),
         TextDrawable::new(CURRENT_MAGAZINE_TEXT, Position::new(1.9, -1.94)),
-      ]
+      ],
+      menu: vec![
+        TextDrawable::new(GAME_TITLE, Position::new(0.96, -0.94)),
+        TextDrawable::new("", Position::new(0.96, -0.98)),
+      ],
+      console: Vec::new(),
     }
   }
 }
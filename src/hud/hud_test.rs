@@ -0,0 +1,15 @@
+#[test]
+fn menu_hints_test() {
+  use crate::gfx_app::bindings::Bindings;
+  use crate::hud::MenuHints;
+
+  let hints = MenuHints::default();
+  assert_eq!(hints.main_menu, "Enter - start, Esc - quit");
+  assert_eq!(hints.paused, "Esc - resume, Q - quit");
+  assert_eq!(hints.game_over, "Enter - restart, Q - quit");
+
+  let bindings = Bindings::parse(r#"{ "Pause": ["P"], "Quit": [], "Confirm": ["Space", "Return"] }"#).expect("Bindings should parse");
+  let hints = MenuHints::new(&bindings);
+  assert_eq!(hints.paused, "P - resume", "Unbound actions should be left out");
+  assert_eq!(hints.game_over, "Enter - restart");
+}
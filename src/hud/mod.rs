@@ -4,21 +4,31 @@ use cgmath::Point2;
 use gfx;
 use rusttype::FontCollection;
 use specs;
-use specs::{ReadStorage, WriteStorage};
+use specs::{Read, ReadStorage, WriteStorage};
 
+use crate::assets;
+use crate::assets::AssetManager;
 use crate::character::CharacterDrawable;
+use crate::game::console::Console;
+use crate::game::constants::{CONSOLE_LINES, FONT_PATH, GAME_OVER_TEXT, GAME_TITLE, PAUSED_TEXT};
+use crate::game::state::GameState;
+use crate::gfx_app::bindings::{Action, Bindings};
 use crate::gfx_app::ColorFormat;
 use crate::gfx_app::DepthFormat;
 use crate::graphics::{mesh::RectangularTexturedMesh};
 use crate::graphics::texture::{text_texture, Texture};
-use crate::shaders::{Position, text_pipeline};
+use crate::shaders::{load_pipeline, Position, RenderViews, text_pipeline, TextScale};
 use crate::graphics::mesh::Geometry;
 
 pub mod font;
 pub mod hud_objects;
+mod hud_test;
 
-const SHADER_VERT: &[u8] = include_bytes!("../shaders/text.v.glsl");
-const SHADER_FRAG: &[u8] = include_bytes!("../shaders/text.f.glsl");
+const SHADER_VERT: &str = "shaders/text.v.glsl";
+const SHADER_FRAG: &str = "shaders/text.f.glsl";
+
+const TEXT_SCALE: [f32; 2] = [25.0, 50.0];
+const GLYPH_HEIGHT: f32 = 0.04;
 
 pub struct TextDrawable {
   text: String,
@@ -45,11 +55,13 @@ impl specs::prelude::Component for TextDrawable {
 pub struct TextDrawSystem<R: gfx::Resources> {
   bundle: gfx::pso::bundle::Bundle<R, text_pipeline::Data<R>>,
   texture_cache: HashMap<String, Texture<R>>,
+  glyph_cache: HashMap<String, Texture<R>>,
   pub current_text: String,
 }
 
 impl<R: gfx::Resources> TextDrawSystem<R> {
   pub fn new<F>(factory: &mut F,
+                assets: &mut AssetManager,
                 texts: &[&str],
                 current_text: &str,
                 rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
@@ -57,17 +69,20 @@ impl<R: gfx::Resources> TextDrawSystem<R> {
     where F: gfx::Factory<R> {
     use gfx::traits::FactoryExt;
 
-    let font_bytes = &include_bytes!("../../assets/DejaVuSans.ttf")[..];
-    let font = FontCollection::from_bytes(font_bytes as &[u8])
+    let font = FontCollection::from_bytes(assets.bytes(FONT_PATH).to_vec())
       .unwrap_or_else(|e| panic!("Font loading error: {}", e))
       .into_font().unwrap_or_else(|e| panic!("into_font error: {}", e));
 
     let mut texture_cache: HashMap<String, Texture<R>> = HashMap::new();
+    let mut glyph_cache: HashMap<String, Texture<R>> = HashMap::new();
 
     text_texture(factory, &font, texts, &mut texture_cache);
 
-    let pso = factory.create_pipeline_simple(SHADER_VERT, SHADER_FRAG, text_pipeline::new())
-      .expect("HUD shader loading error");
+    let glyphs = (' '..='~').map(|c| c.to_string()).collect::<Vec<String>>();
+    text_texture(factory, &font, &glyphs.iter().map(String::as_str).collect::<Vec<&str>>(), &mut glyph_cache);
+
+    let pso = load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, text_pipeline::new())
+      .unwrap_or_else(|e| panic!("HUD shader loading error: {}", e));
 
     let texture = texture_cache[current_text].clone();
 
@@ -76,6 +91,7 @@ impl<R: gfx::Resources> TextDrawSystem<R> {
     let pipeline_data = text_pipeline::Data {
       vbuf: rect_mesh.mesh.vertex_buffer,
       position_cb: factory.create_constant_buffer(1),
+      scale_cb: factory.create_constant_buffer(1),
       text_sheet: (rect_mesh.mesh.texture.raw, factory.create_sampler_linear()),
       out_color: rtv,
       out_depth: dsv,
@@ -84,37 +100,123 @@ impl<R: gfx::Resources> TextDrawSystem<R> {
     TextDrawSystem {
       bundle: gfx::Bundle::new(rect_mesh.mesh.slice, pso, pipeline_data),
       texture_cache,
+      glyph_cache,
       current_text: current_text.to_string(),
     }
   }
 
+  pub fn update_views(&mut self,
+                      rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+                      dsv: gfx::handle::DepthStencilView<R, DepthFormat>) {
+    self.bundle.data.set_views(rtv, dsv);
+  }
+
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, changed: &[String])
+    where F: gfx::Factory<R> {
+    if let Some(pso) = assets::reload(changed, &[SHADER_VERT, SHADER_FRAG], || load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, text_pipeline::new())) {
+      self.bundle.pso = pso;
+    }
+  }
+
   pub fn draw<C>(&mut self,
                  drawable: &TextDrawable,
                  encoder: &mut gfx::Encoder<R, C>)
     where C: gfx::CommandBuffer<R> {
+    if !self.texture_cache.contains_key(&drawable.text) {
+      self.draw_glyphs(drawable, encoder);
+      return;
+    }
     encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.position);
+    encoder.update_constant_buffer(&self.bundle.data.scale_cb, &TextScale::new(TEXT_SCALE[0], TEXT_SCALE[1]));
     if self.current_text.trim() != drawable.text.trim() {
       self.current_text = drawable.text.to_owned();
       self.bundle.data.text_sheet.0 = self.texture_cache[&drawable.text].raw.clone();
     }
     self.bundle.encode(encoder);
   }
+
+  fn draw_glyphs<C>(&mut self,
+                    drawable: &TextDrawable,
+                    encoder: &mut gfx::Encoder<R, C>)
+    where C: gfx::CommandBuffer<R> {
+    let mut position = drawable.position;
+    for c in drawable.text.chars() {
+      if let Some(glyph) = self.glyph_cache.get(&c.to_string()) {
+        let width = GLYPH_HEIGHT * glyph.size.x as f32 / glyph.size.y as f32;
+        encoder.update_constant_buffer(&self.bundle.data.position_cb, &position);
+        encoder.update_constant_buffer(&self.bundle.data.scale_cb, &TextScale::new(2.0 / width, 2.0 / GLYPH_HEIGHT));
+        self.bundle.data.text_sheet.0 = glyph.raw.clone();
+        self.bundle.encode(encoder);
+        position = position + Position::new(width, 0.0);
+      }
+    }
+    self.current_text.clear();
+  }
+}
+
+// Menu hints name the inputs currently bound to the menu actions
+pub struct MenuHints {
+  pub main_menu: String,
+  pub paused: String,
+  pub game_over: String,
+}
+
+impl MenuHints {
+  pub fn new(bindings: &Bindings) -> MenuHints {
+    let hint = |hints: &[(Action, &str)]| hints.iter()
+      .filter_map(|(action, text)| bindings.input_name(*action).map(|input| format!("{} - {}", input, text)))
+      .collect::<Vec<String>>()
+      .join(", ");
+    MenuHints {
+      main_menu: hint(&[(Action::Confirm, "start"), (Action::Pause, "quit")]),
+      paused: hint(&[(Action::Pause, "resume"), (Action::Quit, "quit")]),
+      game_over: hint(&[(Action::Confirm, "restart"), (Action::Quit, "quit")]),
+    }
+  }
+}
+
+impl Default for MenuHints {
+  fn default() -> MenuHints {
+    MenuHints::new(&Bindings::new())
+  }
 }
 
 pub struct PreDrawSystem;
 
 impl<'a> specs::prelude::System<'a> for PreDrawSystem {
   type SystemData = (ReadStorage<'a, CharacterDrawable>,
-                     WriteStorage<'a, hud_objects::HudObjects>);
+                     WriteStorage<'a, hud_objects::HudObjects>,
+                     Read<'a, GameState>,
+                     Read<'a, Console>,
+                     Read<'a, MenuHints>);
 
-  fn run(&mut self, (character_drawable, mut hud_objects): Self::SystemData) {
+  fn run(&mut self, (character_drawable, mut hud_objects, state, console, hints): Self::SystemData) {
     use specs::join::Join;
 
+    let menu_texts = match *state {
+      GameState::Paused => (PAUSED_TEXT, &hints.paused),
+      GameState::GameOver => (GAME_OVER_TEXT, &hints.game_over),
+      _ => (GAME_TITLE, &hints.main_menu),
+    };
+
     for (cd, huds) in (&character_drawable, &mut hud_objects).join() {
       let new_ammo_text = format!("Ammo {}", cd.stats.ammunition);
       let new_mag_text = format!("Magazines {}/2", cd.stats.magazines);
       huds.objects[1].update(new_ammo_text);
       huds.objects[2].update(new_mag_text);
+      huds.menu[0].update(menu_texts.0.to_string());
+      huds.menu[1].update(menu_texts.1.to_string());
+
+      huds.console.clear();
+      if console.is_open {
+        let prompt = format!("> {}_", console.input);
+        let lines = console.lines.iter()
+          .skip(console.lines.len().saturating_sub(CONSOLE_LINES))
+          .chain(std::iter::once(&prompt));
+        for (idx, line) in lines.enumerate() {
+          huds.console.push(TextDrawable::new(line, Position::new(0.02, -0.02 - idx as f32 * 0.05)));
+        }
+      }
     }
   }
 }
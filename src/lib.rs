@@ -0,0 +1,18 @@
+#[macro_use]
+extern crate gfx;
+
+pub mod assets;
+mod audio;
+mod bullet;
+pub mod gfx_app;
+pub mod game;
+mod data;
+mod critter;
+pub mod graphics;
+mod hud;
+mod terrain_object;
+mod terrain_shape;
+mod terrain;
+mod character;
+mod shaders;
+mod zombie;
@@ -1,41 +1,55 @@
 extern crate getopts;
-#[macro_use]
-extern crate gfx;
+
+use std::path::PathBuf;
 
 use getopts::Options;
 
-use crate::game::constants::{GAME_TITLE, GAME_VERSION};
-use crate::gfx_app::GameOptions;
-
-mod audio;
-mod bullet;
-mod gfx_app;
-mod game;
-mod data;
-mod critter;
-pub mod graphics;
-mod hud;
-mod terrain_object;
-mod terrain_shape;
-mod terrain;
-mod character;
-mod shaders;
-mod zombie;
+use hinterland::assets::{AssetManager, archive::AssetArchive};
+use hinterland::game::{constants::{ASSETS_ROOT, BINDINGS_FILE_PATH, GAME_TITLE, GAME_VERSION, HEADLESS_TICKS}, headless, random_seed, settings::{default_settings_path, Settings}};
+use hinterland::gfx_app;
+use hinterland::gfx_app::GameOptions;
+use hinterland::gfx_app::replay::Replay;
 
 fn print_usage() {
-  println!("USAGE:\nhinterland [FLAGS]\n\nFLAGS:\n-h, --help\t\t\tPrints help information\n-v, --version\t\t\tPrints version information\n-w, --windowed_mode\t\tRun game in windowed mode");
+  println!("USAGE:\nhinterland [FLAGS] [OPTIONS]\n\nFLAGS:\n-h, --help\t\t\tPrints help information\n-v, --version\t\t\tPrints version information\n-w, --windowed_mode\t\tRun game in windowed mode\n    --headless\t\t\tRun game logic without window, rendering or audio\n    --save_settings\t\tSave command line options to settings file\n\nOPTIONS:\n    --tick_rate <HZ>\t\tGame logic updates per second\n    --ticks <TICKS>\t\tNumber of logic ticks to simulate in headless mode\n    --seed <SEED>\t\tSeed for the game random number generator\n    --record <FILE>\t\tRecord player input to replay file\n    --replay <FILE>\t\tPlay back player input from replay file\n    --bindings <FILE>\t\tKeyboard and mouse bindings file, defaults to bindings.json\n    --settings <FILE>\t\tSettings file, defaults to $XDG_CONFIG_HOME/hinterland/settings.json\n    --assets <PATH>\t\tAssets directory or archive, defaults to $HINTERLAND_ASSETS, then assets next to the executable, then in the working directory\n    --pack_assets <FILE>\tPack the assets directory into an archive file");
 }
 
 fn print_version() {
   println!("{} - {}", GAME_TITLE, GAME_VERSION)
 }
 
+fn pack_assets(assets: Option<String>, file: &str) {
+  let root = assets.map_or_else(|| PathBuf::from(ASSETS_ROOT), PathBuf::from);
+  let archive = match AssetArchive::pack(&root) {
+    Ok(a) => a,
+    Err(e) => panic!("Packing assets failed: {}", e),
+  };
+  if let Err(e) = std::fs::write(file, &archive) {
+    panic!("Asset archive {} write failed: {}", file, e);
+  }
+  if let Err(e) = AssetManager::open(PathBuf::from(file).as_path()) {
+    panic!("Asset archive {} is not readable: {}", file, e);
+  }
+  println!("Packed {} into {}", root.display(), file);
+}
+
 pub fn main() {
   let args = std::env::args().collect::<Vec<String>>();
   let mut opts = Options::new();
   opts.optflag("w", "windowed_mode", "Run game in windowed mode");
   opts.optflag("h", "help", "Prints help information");
   opts.optflag("v", "version", "Prints version information");
+  opts.optflag("", "headless", "Run game logic without window, rendering or audio");
+  opts.optopt("", "tick_rate", "Game logic updates per second", "HZ");
+  opts.optopt("", "ticks", "Number of logic ticks to simulate in headless mode", "TICKS");
+  opts.optopt("", "seed", "Seed for the game random number generator", "SEED");
+  opts.optopt("", "record", "Record player input to replay file", "FILE");
+  opts.optopt("", "replay", "Play back player input from replay file", "FILE");
+  opts.optopt("", "bindings", "Keyboard and mouse bindings file", "FILE");
+  opts.optopt("", "settings", "Settings file", "FILE");
+  opts.optopt("", "assets", "Assets directory or archive", "PATH");
+  opts.optopt("", "pack_assets", "Pack the assets directory into an archive file", "FILE");
+  opts.optflag("", "save_settings", "Save command line options to settings file");
 
   let matches = match opts.parse(&args[1..]) {
     Ok(matching_args) => { matching_args }
@@ -52,7 +66,65 @@ pub fn main() {
     return;
   }
 
-  let game_opt = GameOptions::new(matches.opt_present("windowed_mode"));
-  let mut window = gfx_app::WindowContext::new(game_opt);
-  gfx_app::init::run(&mut window);
+  if let Some(file) = matches.opt_str("pack_assets") {
+    pack_assets(matches.opt_str("assets"), &file);
+    return;
+  }
+
+  // Headless runs use the defaults and never touch the settings file
+  let headless = matches.opt_present("headless");
+  let settings_path = matches.opt_str("settings").map_or_else(default_settings_path, PathBuf::from);
+  let mut settings = if headless { Settings::new() } else { Settings::load(&settings_path) };
+
+  if matches.opt_present("windowed_mode") {
+    settings.windowed_mode = true;
+  }
+
+  settings.tick_rate = match matches.opt_get_default("tick_rate", settings.tick_rate) {
+    Ok(val) if val > 0.0 => val,
+    Ok(val) => panic!("Invalid tick_rate value: {}", val),
+    Err(err) => panic!("Invalid tick_rate value: {}", err),
+  };
+
+  if matches.opt_present("save_settings") && !headless {
+    settings.save(&settings_path);
+  }
+
+  let seed = match matches.opt_get_default("seed", random_seed()) {
+    Ok(val) => val,
+    Err(err) => panic!("Invalid seed value: {}", err),
+  };
+
+  let replay = matches.opt_str("replay").map(|path| Replay::load(&path));
+
+  if headless {
+    let ticks = match matches.opt_get("ticks") {
+      Ok(val) => val,
+      Err(err) => panic!("Invalid ticks value: {}", err),
+    };
+    let mut options = match replay {
+      Some(r) => headless::HeadlessOptions::from_replay(ticks, r),
+      None => headless::HeadlessOptions::new(ticks.unwrap_or(HEADLESS_TICKS), settings.tick_rate, seed),
+    };
+    options.assets = matches.opt_str("assets");
+    let summary = headless::run(options);
+    println!("{}", summary);
+    return;
+  }
+
+  let seed = match replay {
+    Some(ref r) => {
+      settings.tick_rate = r.header.tick_rate;
+      r.header.seed
+    }
+    None => seed,
+  };
+  let game_opt = GameOptions::new(settings,
+                                  settings_path,
+                                  seed,
+                                  matches.opt_str("record"),
+                                  matches.opt_str("bindings").unwrap_or_else(|| BINDINGS_FILE_PATH.to_string()),
+                                  matches.opt_str("assets"));
+  let mut window = gfx_app::WindowContext::new(game_opt.clone());
+  gfx_app::init::run(&mut window, &game_opt, replay);
 }
@@ -3,16 +3,20 @@ use std::{fmt::{Display, Formatter, Result}, ops::{Add, Sub}};
 
 use cgmath::BaseFloat;
 use gfx;
+use gfx::traits::FactoryExt;
 
-gfx_defines! {
-  constant TileMapData {
-    data: [f32; 4] = "data",
-  }
+use crate::assets::AssetManager;
+use crate::gfx_app::{ColorFormat, DepthFormat};
 
+gfx_defines! {
   constant Position {
     position: [f32; 2] = "a_position",
   }
 
+  constant TextScale {
+    scale: [f32; 2] = "a_scale",
+  }
+
   constant Rotation {
     rotation: f32 = "a_rotation",
   }
@@ -24,6 +28,7 @@ gfx_defines! {
   constant TilemapSettings {
     world_size: [f32; 2] = "u_WorldSize",
     tilesheet_size: [f32; 2] = "u_TilesheetSize",
+    opacity: f32 = "u_Opacity",
   }
 
   vertex VertexData {
@@ -32,10 +37,11 @@ gfx_defines! {
   }
 
   constant CharacterSheet {
-    x_div: f32 = "x_div",
-    y_div: f32 = "y_div",
-    row_idx: u32 = "a_row",
-    index: f32 = "a_index",
+    uv: [f32; 4] = "a_uv",
+    trim: [f32; 4] = "a_trim",
+    offset: [f32; 2] = "a_offset",
+    rotated: f32 = "a_rotated",
+    alpha: f32 = "a_alpha",
   }
 
   pipeline bullet_pipeline {
@@ -53,7 +59,7 @@ gfx_defines! {
     position_cb: gfx::ConstantBuffer<Position> = "b_CharacterPosition",
     character_sprite_cb: gfx::ConstantBuffer<CharacterSheet> = "b_CharacterSprite",
     charactersheet: gfx::TextureSampler<[f32; 4]> = "t_CharacterSheet",
-    out_color: gfx::RenderTarget<gfx::format::Rgba8> = "Target0",
+    out_color: gfx::BlendTarget<gfx::format::Rgba8> = ("Target0", gfx::state::ColorMask::all(), gfx::preset::blend::ALPHA),
     out_depth: gfx::DepthTarget<gfx::format::DepthStencil> = gfx::preset::depth::LESS_EQUAL_WRITE,
   }
 
@@ -62,10 +68,10 @@ gfx_defines! {
     position_cb: gfx::ConstantBuffer<Position> = "b_TileMapPosition",
     time_passed_cb: gfx::ConstantBuffer<Time> = "b_TimeModulo",
     projection_cb: gfx::ConstantBuffer<Projection> = "b_VsLocals",
-    tilemap: gfx::ConstantBuffer<TileMapData> = "b_TileMap",
+    tilemap: gfx::TextureSampler<f32> = "t_TileMap",
     tilemap_cb: gfx::ConstantBuffer<TilemapSettings> = "b_PsLocals",
     tilesheet: gfx::TextureSampler<[f32; 4]> = "t_TileSheet",
-    out_color: gfx::RenderTarget<gfx::format::Rgba8> = "Target0",
+    out_color: gfx::BlendTarget<gfx::format::Rgba8> = ("Target0", gfx::state::ColorMask::all(), gfx::preset::blend::ALPHA),
     out_depth: gfx::DepthTarget<gfx::format::DepthStencil> = gfx::preset::depth::LESS_EQUAL_WRITE,
   }
 
@@ -82,6 +88,7 @@ gfx_defines! {
   pipeline text_pipeline {
     vbuf: gfx::VertexBuffer<VertexData> = (),
     position_cb: gfx::ConstantBuffer<Position> = "b_TextPosition",
+    scale_cb: gfx::ConstantBuffer<TextScale> = "b_TextScale",
     text_sheet: gfx::TextureSampler<[f32; 4]> = "t_TextSheet",
     out_color: gfx::RenderTarget<gfx::format::Rgba8> = "Target0",
     out_depth: gfx::DepthTarget<gfx::format::DepthStencil> = gfx::preset::depth::LESS_EQUAL_WRITE,
@@ -91,9 +98,42 @@ gfx_defines! {
     model: [[f32; 4]; 4] = "u_Model",
     view: [[f32; 4]; 4] = "u_View",
     proj: [[f32; 4]; 4] = "u_Proj",
+    camera: [[f32; 4]; 4] = "u_Camera",
   }
 }
 
+// Pipelines draw into the main targets, which are recreated when the window is resized
+pub trait RenderViews<R: gfx::Resources> {
+  fn set_views(&mut self,
+               rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+               dsv: gfx::handle::DepthStencilView<R, DepthFormat>);
+}
+
+macro_rules! impl_render_views {
+  ($($pipeline:ident),*) => {
+    $(
+      impl<R: gfx::Resources> RenderViews<R> for $pipeline::Data<R> {
+        fn set_views(&mut self,
+                     rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+                     dsv: gfx::handle::DepthStencilView<R, DepthFormat>) {
+          self.out_color = rtv;
+          self.out_depth = dsv;
+        }
+      }
+    )*
+  };
+}
+
+impl_render_views!(bullet_pipeline, critter_pipeline, tilemap_pipeline, static_element_pipeline, text_pipeline);
+
+pub fn load_pipeline<R, F, I>(factory: &mut F, assets: &mut AssetManager, vert: &str, frag: &str, init: I)
+                              -> std::result::Result<gfx::PipelineState<R, I::Meta>, String>
+  where R: gfx::Resources, F: gfx::Factory<R>, I: gfx::pso::PipelineInit {
+  let (vert_bytes, frag_bytes) = (assets.try_bytes(vert)?, assets.try_bytes(frag)?);
+  factory.create_pipeline_simple(&vert_bytes, &frag_bytes, init)
+    .map_err(|e| format!("Shader {} / {} error: {:?}", vert, frag, e))
+}
+
 impl Time {
   pub fn new(time_passed: u64) -> Time {
     let time_modulo = (time_passed % 89) as f32;
@@ -103,6 +143,14 @@ impl Time {
   }
 }
 
+impl TextScale {
+  pub fn new(x: f32, y: f32) -> TextScale {
+    TextScale {
+      scale: [x, y]
+    }
+  }
+}
+
 impl Rotation {
   pub fn new(rotation: f32) -> Rotation {
     Rotation {
@@ -128,16 +176,6 @@ impl Iterator for VertexData {
   }
 }
 
-impl TileMapData {
-  pub fn new_empty() -> TileMapData {
-    TileMapData { data: [32.0, 32.0, 0.0, 0.0] }
-  }
-
-  pub fn new(data: [f32; 4]) -> TileMapData {
-    TileMapData { data }
-  }
-}
-
 impl Position {
   pub fn new<T: BaseFloat>(x: T, y: T) -> Position where f32: std::convert::From<T> {
     Position { position: [f32::from(x), f32::from(y)] }
@@ -159,6 +197,10 @@ impl Position {
     self.position[1]
   }
 
+  pub fn lerp(self, other: Position, alpha: f32) -> Position {
+    Position::new(self.x() + (other.x() - self.x()) * alpha, self.y() + (other.y() - self.y()) * alpha)
+  }
+
   pub fn tile_center(self, x: f32, y:f32) -> Position {
     let new_x = if self.position[0] < 0.0 { self.position[0] - x } else { self.position[0] + x };
     let new_y = if self.position[1] < 0.0 { self.position[1] - y } else { self.position[1] + y };
@@ -166,6 +208,10 @@ impl Position {
   }
 }
 
+impl specs::prelude::Component for Position {
+  type Storage = specs::storage::VecStorage<Position>;
+}
+
 impl Add for Position {
   type Output = Position;
 
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use cgmath::Point2;
+use tiled::{Map, PropertyValue};
+
+use crate::data::get_map_tile;
+use crate::graphics::MapSize;
+use crate::terrain::map_objects::MapObjects;
+use crate::terrain::tile_map::TILE_GID_MASK;
+
+// Blocked map tiles, built from tiles with a blocked property in any layer and from object footprints
+pub struct CollisionGrid {
+  size: MapSize,
+  blocked: Vec<bool>,
+}
+
+impl CollisionGrid {
+  pub fn empty(size: MapSize) -> CollisionGrid {
+    CollisionGrid {
+      size,
+      blocked: vec![false; size.width * size.height],
+    }
+  }
+
+  // Hidden layers count too, so maps can keep collision in a layer that is not drawn
+  pub fn from_map(map: &Map, objects: &MapObjects) -> Result<CollisionGrid, String> {
+    let mut grid = CollisionGrid::empty(MapSize::new(map.width as usize, map.height as usize));
+    let blocked_gids = blocked_gids(map)?;
+
+    for (layer_idx, layer) in map.layers.iter().enumerate() {
+      if layer.tiles.len() != grid.size.height || layer.tiles.iter().any(|row| row.len() != grid.size.width) {
+        return Err(format!("Layer {} should be {}x{} tiles", layer.name, grid.size.width, grid.size.height));
+      }
+      for y_pos in 0..grid.size.height {
+        for x_pos in 0..grid.size.width {
+          let gid = get_map_tile(map, layer_idx, x_pos, y_pos) & TILE_GID_MASK;
+          if blocked_gids.contains(&gid) {
+            grid.block(Point2::new(x_pos as i32, y_pos as i32));
+          }
+        }
+      }
+    }
+
+    for tile in &objects.blocked_tiles {
+      grid.block(*tile);
+    }
+    Ok(grid)
+  }
+
+  fn index(&self, tile: Point2<i32>) -> Option<usize> {
+    if tile.x < 0 || tile.y < 0 || tile.x as usize >= self.size.width || tile.y as usize >= self.size.height {
+      None
+    } else {
+      Some(tile.y as usize * self.size.width + tile.x as usize)
+    }
+  }
+
+  pub fn size(&self) -> MapSize {
+    self.size
+  }
+
+  pub fn block(&mut self, tile: Point2<i32>) {
+    if let Some(idx) = self.index(tile) {
+      self.blocked[idx] = true;
+    }
+  }
+
+  // Tiles outside of the map are blocked
+  pub fn is_blocked(&self, tile: Point2<i32>) -> bool {
+    self.index(tile).is_none_or(|idx| self.blocked[idx])
+  }
+}
+
+fn blocked_gids(map: &Map) -> Result<HashSet<u32>, String> {
+  let mut gids = HashSet::new();
+  for tile_set in &map.tilesets {
+    for tile in &tile_set.tiles {
+      match tile.properties.get("blocked") {
+        None | Some(PropertyValue::BoolValue(false)) => {}
+        Some(PropertyValue::BoolValue(true)) => {
+          gids.insert(tile_set.first_gid + tile.id);
+        }
+        Some(value) => return Err(format!("Invalid blocked value for tile {} of tile set {}: {:?}", tile.id, tile_set.name, value)),
+      }
+    }
+  }
+  Ok(gids)
+}
@@ -0,0 +1,56 @@
+#[test]
+fn collision_grid_test() {
+  use std::io::Cursor;
+
+  use cgmath::Point2;
+
+  use crate::terrain::collision::CollisionGrid;
+  use crate::terrain::map_objects::MapObjects;
+  use crate::terrain::path_finding::calc_route;
+
+  // Rows are listed top down, so the last one is tile row 0
+  let map = tiled::parse(Cursor::new(r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.2" orientation="isometric" renderorder="left-down" width="4" height="4" tilewidth="64" tileheight="32">
+ <tileset firstgid="1" name="ground" tilewidth="64" tileheight="32">
+  <image source="ground.png" width="128" height="32"/>
+  <tile id="1">
+   <properties>
+    <property name="blocked" type="bool" value="true"/>
+   </properties>
+  </tile>
+ </tileset>
+ <layer name="ground" width="4" height="4">
+  <data encoding="csv">
+1,1,1,1,
+1,1,1,1,
+1,2,1,1,
+1,2,1,1
+</data>
+ </layer>
+ <layer name="collision" width="4" height="4" visible="0">
+  <data encoding="csv">
+2,0,0,0,
+0,0,0,0,
+0,0,0,0,
+0,0,0,0
+</data>
+ </layer>
+ <objectgroup name="objects">
+  <object id="1" type="player_start" x="16" y="16"/>
+  <object id="2" type="house" x="96" y="96"><point/></object>
+ </objectgroup>
+</map>"#)).expect("Map should parse");
+  let objects = MapObjects::from_map(&map).expect("Objects should load");
+  let grid = CollisionGrid::from_map(&map, &objects).expect("Collision should load");
+
+  let blocked = (0..4).flat_map(|y| (0..4).map(move |x| Point2::new(x, y)))
+    .filter(|tile| grid.is_blocked(*tile))
+    .map(|tile| (tile.x, tile.y))
+    .collect::<Vec<_>>();
+  assert_eq!(blocked, [(1, 0), (2, 0), (3, 0), (1, 1), (2, 1), (3, 1), (0, 3)], "Blocked tiles, hidden layers and the house footprint should block");
+  assert!(grid.is_blocked(Point2::new(-1, 0)), "Tiles outside of the map should be blocked");
+
+  let (route, _) = calc_route(grid.size().tile_to_coords(0, 0), grid.size().tile_to_coords(3, 3), &grid).expect("Route should be found");
+  assert!(route.iter().all(|tile| !grid.is_blocked(*tile)), "Route should avoid blocked tiles");
+  assert_eq!(route.last(), Some(&Point2::new(3, 3)));
+}
@@ -0,0 +1,143 @@
+use cgmath::Point2;
+use tiled::{Map, Object, ObjectShape, PropertyValue};
+
+use crate::game::constants::{EXIT_RADIUS, HOUSE_FOOTPRINT, TILE_SIZE, TREE_FOOTPRINT, ZOMBIE_SPAWN_RADIUS};
+use crate::graphics::{MapSize, position_distance};
+use crate::shaders::Position;
+use crate::terrain_object::{TerrainObjectDrawable, TerrainTexture};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZombieSpawn {
+  pub position: Position,
+  pub radius: f32,
+}
+
+// Reaching an exit ends the level
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitZone {
+  pub position: Position,
+  pub radius: f32,
+}
+
+impl ExitZone {
+  pub fn contains(&self, position: Position) -> bool {
+    position_distance(self.position, position) < self.radius * TILE_SIZE
+  }
+}
+
+// Objects placed in the map object layers, the object type selects what gets built
+pub struct MapObjects {
+  pub objects: Vec<TerrainObjectDrawable>,
+  pub hills: Vec<Position>,
+  pub zombie_spawns: Vec<ZombieSpawn>,
+  pub exits: Vec<ExitZone>,
+  pub player_start: Position,
+  pub blocked_tiles: Vec<Point2<i32>>,
+}
+
+impl MapObjects {
+  pub fn from_map(map: &Map) -> Result<MapObjects, String> {
+    let mut map_objects = MapObjects {
+      objects: Vec::new(),
+      hills: Vec::new(),
+      zombie_spawns: Vec::new(),
+      exits: Vec::new(),
+      player_start: Position::origin(),
+      blocked_tiles: Vec::new(),
+    };
+    let mut player_starts = 0;
+    let size = MapSize::new(map.width as usize, map.height as usize);
+
+    let objects = map.object_groups.iter()
+      .filter(|group| group.visible)
+      .flat_map(|group| group.objects.iter())
+      .filter(|object| object.visible);
+    for object in objects {
+      let tile = object_tile(map, object);
+      let position = size.map_to_coords(tile.x, tile.y);
+      match object.obj_type.as_str() {
+        "ammo" => {
+          let mut ammo = TerrainObjectDrawable::new(position, TerrainTexture::Ammo);
+          if let Some(magazines) = int_property(object, "magazines")? {
+            ammo.magazines = magazines as usize;
+          }
+          map_objects.objects.push(ammo);
+        }
+        "house" => {
+          map_objects.objects.push(TerrainObjectDrawable::new(position, TerrainTexture::House));
+          map_objects.block_footprint(tile, int_property(object, "footprint")?.unwrap_or(HOUSE_FOOTPRINT));
+        }
+        "tree" => {
+          map_objects.objects.push(TerrainObjectDrawable::new(position, TerrainTexture::Tree));
+          map_objects.block_footprint(tile, int_property(object, "footprint")?.unwrap_or(TREE_FOOTPRINT));
+        }
+        "hill" => map_objects.hills.push(position),
+        "zombie_spawn" => map_objects.zombie_spawns.push(ZombieSpawn {
+          position,
+          radius: float_property(object, "radius")?.unwrap_or(ZOMBIE_SPAWN_RADIUS),
+        }),
+        "exit" => map_objects.exits.push(ExitZone {
+          position,
+          radius: float_property(object, "radius")?.unwrap_or(EXIT_RADIUS),
+        }),
+        "player_start" => {
+          map_objects.player_start = position;
+          player_starts += 1;
+        }
+        "" => return Err(format!("Object {} is missing a type", object_name(object))),
+        other => return Err(format!("Unknown type {} for object {}", other, object_name(object))),
+      }
+    }
+
+    if player_starts != 1 {
+      return Err(format!("Map should have one player_start object, found {}", player_starts));
+    }
+    Ok(map_objects)
+  }
+
+  // Footprints are squares of tiles around the object
+  fn block_footprint(&mut self, tile: Point2<f32>, size: i32) {
+    let start = Point2::new((tile.x - size as f32 / 2.0).round() as i32, (tile.y - size as f32 / 2.0).round() as i32);
+    for y in start.y..start.y + size {
+      for x in start.x..start.x + size {
+        self.blocked_tiles.push(Point2::new(x, y));
+      }
+    }
+  }
+}
+
+// Isometric object coordinates are in tile heights along both axes, rows are flipped like the tile layers
+fn object_tile(map: &Map, object: &Object) -> Point2<f32> {
+  let (x, y) = match object.shape {
+    ObjectShape::Rect { width, height } | ObjectShape::Ellipse { width, height } =>
+      (object.x + width / 2.0, object.y + height / 2.0),
+    _ => (object.x, object.y),
+  };
+  let tile_size = map.tile_height as f32;
+  Point2::new(x / tile_size, map.height as f32 - y / tile_size)
+}
+
+fn object_name(object: &Object) -> String {
+  if object.name.is_empty() {
+    format!("#{}", object.id)
+  } else {
+    object.name.clone()
+  }
+}
+
+fn int_property(object: &Object, key: &str) -> Result<Option<i32>, String> {
+  match object.properties.get(key) {
+    None => Ok(None),
+    Some(PropertyValue::IntValue(value)) if *value >= 0 => Ok(Some(*value)),
+    Some(value) => Err(format!("Invalid {} value for object {}: {:?}", key, object_name(object), value)),
+  }
+}
+
+fn float_property(object: &Object, key: &str) -> Result<Option<f32>, String> {
+  match object.properties.get(key) {
+    None => Ok(None),
+    Some(PropertyValue::FloatValue(value)) if *value >= 0.0 => Ok(Some(*value)),
+    Some(PropertyValue::IntValue(value)) if *value >= 0 => Ok(Some(*value as f32)),
+    Some(value) => Err(format!("Invalid {} value for object {}: {:?}", key, object_name(object), value)),
+  }
+}
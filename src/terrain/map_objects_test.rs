@@ -0,0 +1,54 @@
+#[test]
+fn map_objects_test() {
+  use std::io::Cursor;
+
+  use crate::game::constants::{AMMO_PICKUP_MAGAZINES, ZOMBIE_SPAWN_RADIUS};
+  use crate::graphics::MapSize;
+  use crate::shaders::Position;
+  use crate::terrain::map_objects::{ExitZone, MapObjects, ZombieSpawn};
+  use crate::terrain_object::TerrainTexture;
+
+  let map = |objects: &str| {
+    let map = format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.2" orientation="isometric" renderorder="left-down" width="128" height="128" tilewidth="64" tileheight="32">
+ <objectgroup name="objects">{}</objectgroup>
+ <objectgroup name="hidden" visible="0">
+  <object id="9" type="tree" x="0" y="0"/>
+ </objectgroup>
+</map>"#, objects);
+    tiled::parse(Cursor::new(map)).expect("Map should parse")
+  };
+
+  let objects = MapObjects::from_map(&map(r#"
+  <object id="1" type="player_start" x="2048" y="2048"><point/></object>
+  <object id="2" type="ammo" x="1776" y="1296"><point/></object>
+  <object id="3" type="ammo" x="0" y="0"><properties><property name="magazines" type="int" value="5"/></properties></object>
+  <object id="4" type="house" x="1760" y="2304" width="64" height="64"/>
+  <object id="5" type="hill" x="2080" y="2144"><point/></object>
+  <object id="6" type="zombie_spawn" x="320" y="3776"><properties><property name="radius" type="float" value="2.5"/></properties></object>
+  <object id="7" type="zombie_spawn" x="320" y="320"/>
+  <object id="8" type="exit" x="2048" y="2048"><properties><property name="radius" type="float" value="2"/></properties></object>"#)).expect("Objects should load");
+
+  let size = MapSize::new(128, 128);
+  assert_eq!(objects.player_start, Position::origin(), "Map center should be the world origin");
+  assert_eq!(size.map_to_coords(56.5, 55.5), size.tile_to_coords(56, 55), "Tile centers should match tile_to_coords");
+  assert_eq!(objects.objects.iter().map(|o| (o.object_type, o.magazines)).collect::<Vec<_>>(),
+             [(TerrainTexture::Ammo, AMMO_PICKUP_MAGAZINES), (TerrainTexture::Ammo, 5), (TerrainTexture::House, AMMO_PICKUP_MAGAZINES)]);
+  assert_eq!(size.coords_to_tile(objects.objects[0].position), cgmath::Point2::new(55, 87), "Rows should be flipped like tile layers");
+  assert_eq!(size.coords_to_tile(objects.objects[2].position), cgmath::Point2::new(56, 55), "Rectangles should be placed by their center");
+  assert_eq!(objects.hills.len(), 1);
+  assert_eq!(objects.zombie_spawns, [
+    ZombieSpawn { position: size.map_to_coords(10.0, 10.0), radius: 2.5 },
+    ZombieSpawn { position: size.map_to_coords(10.0, 118.0), radius: ZOMBIE_SPAWN_RADIUS },
+  ]);
+
+  assert_eq!(objects.exits, [ExitZone { position: Position::origin(), radius: 2.0 }]);
+  assert!(objects.exits[0].contains(Position::new(90.0, 0.0)) && !objects.exits[0].contains(Position::new(100.0, 0.0)), "Exit radius should be in tiles");
+
+  let error = |objects: &str| MapObjects::from_map(&map(objects)).err().expect("Objects should not load");
+  assert_eq!(error(""), "Map should have one player_start object, found 0");
+  assert_eq!(error(r#"<object id="1" name="barn" type="barn" x="0" y="0"/>"#), "Unknown type barn for object barn");
+  assert_eq!(error(r#"<object id="2" x="0" y="0"/>"#), "Object #2 is missing a type");
+  assert_eq!(error(r#"<object id="1" type="ammo" x="0" y="0"><properties><property name="magazines" value="many"/></properties></object>"#),
+             "Invalid magazines value for object #1: StringValue(\"many\")");
+}
@@ -2,46 +2,68 @@ use cgmath::Point2;
 use genmesh::{generators::{IndexedPolygon, Plane, SharedVertex}, Triangulate, Vertices};
 use gfx;
 use specs;
-use specs::prelude::{Read, ReadStorage, WriteStorage};
+use specs::prelude::{ReadExpect, WriteStorage};
 
+use crate::assets;
+use crate::assets::AssetManager;
 use crate::character::controls::CharacterInputState;
-use crate::game::constants::{ASPECT_RATIO, TILE_SIZE, TILES_PCS_H, TILES_PCS_W, VIEW_DISTANCE};
 use crate::gfx_app::{ColorFormat, DepthFormat};
-use crate::graphics::{camera::CameraInputState, can_move_to_tile, coords_to_tile, dimensions::{Dimensions, get_projection, get_view_matrix}};
-use crate::graphics::mesh::TexturedMesh;
-use crate::graphics::texture::{load_texture, Texture};
-use crate::shaders::{Position, Projection, tilemap_pipeline, TilemapSettings, Time, VertexData};
-
+use crate::graphics::{can_move_to_tile, MapSize};
+use crate::graphics::mesh::PlainMesh;
+use crate::graphics::texture::{load_texture, load_tile_indices, try_load_texture};
+use crate::shaders::{load_pipeline, Position, Projection, RenderViews, tilemap_pipeline, TilemapSettings, Time, VertexData};
+use crate::terrain::collision::CollisionGrid;
+
+pub mod collision;
+mod collision_test;
+pub mod map_objects;
+mod map_objects_test;
 pub mod path_finding;
 pub mod tile_map;
+mod tile_map_test;
+mod terrain_test;
+
+// Mesh space is world space turned a quarter, squashed along world x and stretched along world y
+const MESH_SCALE: f32 = 0.675;
 
 fn cartesian_to_isometric(point_x: f32, point_y: f32) -> (f32, f32) {
   ((point_x - point_y), (point_x + point_y) / (16.0 / 9.0))
 }
 
+pub fn world_to_mesh(position: Position) -> [f32; 2] {
+  [position.y() / MESH_SCALE, position.x() * MESH_SCALE]
+}
+
+// Plane corners become the map corners, positions come from the same transform as MapSize so tiles
+// line up with collision on maps of any shape
+pub fn terrain_vertices(size: &MapSize) -> Vec<VertexData> {
+  Plane::new().shared_vertex_iter()
+    .map(|vertex| {
+      let (raw_x, raw_y) = cartesian_to_isometric(vertex.pos.x, vertex.pos.y);
+      let tile_x = ((raw_x / 4.0 - raw_y / 2.25) + 0.5) * size.width as f32;
+      let tile_y = ((raw_x / 4.0 + raw_y / 2.25) + 0.5) * size.height as f32;
+      VertexData::new(world_to_mesh(size.map_to_coords(tile_x, tile_y)), [tile_x, tile_y])
+    })
+    .collect()
+}
+
 pub struct TerrainDrawable {
-  projection: Projection,
   pub position: Position,
   pub tile_position: Point2<i32>,
 }
 
 impl TerrainDrawable {
-  pub fn new() -> TerrainDrawable {
-    let view = get_view_matrix(VIEW_DISTANCE);
-    let projection = get_projection(view, ASPECT_RATIO);
+  pub fn new(tile_position: Point2<i32>) -> TerrainDrawable {
     TerrainDrawable {
-      projection,
       position: Position::origin(),
-      tile_position: coords_to_tile(Position::origin()),
+      tile_position,
     }
   }
 
-  pub fn update(&mut self, world_to_clip: &Projection, ci: &mut CharacterInputState) {
-    self.projection = *world_to_clip;
-    if can_move_to_tile(ci.movement) {
+  pub fn update(&mut self, ci: &mut CharacterInputState, collision: &CollisionGrid) {
+    if can_move_to_tile(collision, ci.movement) {
       ci.is_colliding = false;
-      self.position = ci.movement;
-      self.tile_position = coords_to_tile(self.position);
+      self.tile_position = collision.size().coords_to_tile(ci.movement);
     } else {
       ci.is_colliding = true;
     }
@@ -52,95 +74,142 @@ impl specs::prelude::Component for TerrainDrawable {
   type Storage = specs::storage::HashMapStorage<TerrainDrawable>;
 }
 
-const SHADER_VERT: &[u8] = include_bytes!("../shaders/terrain.v.glsl");
-const SHADER_FRAG: &[u8] = include_bytes!("../shaders/terrain.f.glsl");
+const SHADER_VERT: &str = "shaders/terrain.v.glsl";
+const SHADER_FRAG: &str = "shaders/terrain.f.glsl";
 
+struct TerrainLayer<R: gfx::Resources> {
+  tile_set: usize,
+  settings: TilemapSettings,
+  data: tilemap_pipeline::Data<R>,
+}
+
+// Layers are drawn in map order and blended over each other, they share the mesh and per frame buffers
 pub struct TerrainDrawSystem<R: gfx::Resources> {
-  bundle: gfx::pso::bundle::Bundle<R, tilemap_pipeline::Data<R>>,
+  slice: gfx::Slice<R>,
+  pso: gfx::PipelineState<R, tilemap_pipeline::Meta>,
+  layers: Vec<TerrainLayer<R>>,
+  tile_sheets: Vec<String>,
+  map: String,
   is_tile_map_dirty: bool,
 }
 
 impl<R: gfx::Resources> TerrainDrawSystem<R> {
   pub fn new<F>(factory: &mut F,
+                assets: &mut AssetManager,
+                terrain: &tile_map::Terrain,
                 rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
                 dsv: gfx::handle::DepthStencilView<R, DepthFormat>)
                 -> TerrainDrawSystem<R>
     where F: gfx::Factory<R> {
     use gfx::traits::FactoryExt;
 
-    let plane = Plane::subdivide(TILES_PCS_W, TILES_PCS_H);
-    let vertex_data: Vec<VertexData> =
-      plane.shared_vertex_iter()
-        .map(|vertex| {
-          let tile_x = TILES_PCS_W as f32;
-          let tile_y = TILES_PCS_H as f32;
-          let (raw_x, raw_y) = cartesian_to_isometric(vertex.pos.x, vertex.pos.y);
-          let vertex_x = (TILE_SIZE * (tile_x as f32) / 1.5) * raw_x;
-          let vertex_y = (TILE_SIZE * (tile_y as f32) / 1.666) * raw_y;
-
-          let (u_pos, v_pos) = ((raw_x / 4.0 - raw_y / 2.25) + 0.5, (raw_x / 4.0 + raw_y / 2.25) + 0.5);
-          let tile_map_x = u_pos * tile_x as f32;
-          let tile_map_y = v_pos * tile_y as f32;
-
-          VertexData::new([vertex_x, vertex_y], [tile_map_x, tile_map_y])
-        })
-        .collect();
+    // Positions and tile coordinates are linear over the plane, so one quad covers the whole map
+    let vertex_data = terrain_vertices(&terrain.size);
 
     let index_data =
-      plane.indexed_polygon_iter()
+      Plane::new().indexed_polygon_iter()
         .triangulate()
         .vertices()
         .map(|i| i as u16)
         .collect::<Vec<u16>>();
 
-    let tile_sheet_bytes = &include_bytes!("../../assets/maps/terrain.png")[..];
-    let tile_texture = load_texture(factory, tile_sheet_bytes);
+    let mesh = PlainMesh::new(factory, &vertex_data.as_slice(), index_data.as_slice());
+
+    let pso = load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, tilemap_pipeline::new())
+      .unwrap_or_else(|e| panic!("Terrain shader loading error: {}", e));
+
+    let tile_textures = terrain.tile_sets.iter()
+      .map(|tile_set| load_texture(factory, &assets.bytes(&tile_set.image)))
+      .collect::<Vec<_>>();
+
+    let position_cb = factory.create_constant_buffer(1);
+    let time_passed_cb = factory.create_constant_buffer(1);
+    let projection_cb = factory.create_constant_buffer(1);
+    let sampler = factory.create_sampler_linear();
+    let tile_map_sampler = factory.create_sampler(gfx::texture::SamplerInfo::new(gfx::texture::FilterMethod::Scale, gfx::texture::WrapMode::Clamp));
+
+    let layers = terrain.layers.iter()
+      .map(|layer| TerrainLayer {
+        tile_set: layer.tile_set,
+        settings: TilemapSettings {
+          world_size: [terrain.size.width as f32, terrain.size.height as f32],
+          tilesheet_size: terrain.tile_sets[layer.tile_set].size,
+          opacity: layer.opacity,
+        },
+        data: tilemap_pipeline::Data {
+          vbuf: mesh.vertex_buffer.clone(),
+          position_cb: position_cb.clone(),
+          time_passed_cb: time_passed_cb.clone(),
+          projection_cb: projection_cb.clone(),
+          tilemap: (load_tile_indices(factory, terrain.size, &layer.tiles), tile_map_sampler.clone()),
+          tilemap_cb: factory.create_constant_buffer(1),
+          tilesheet: (tile_textures[layer.tile_set].clone(), sampler.clone()),
+          out_color: rtv.clone(),
+          out_depth: dsv.clone(),
+        },
+      })
+      .collect();
 
-    let mesh = TexturedMesh::new(factory, &vertex_data.as_slice(), index_data.as_slice(), Texture::new(tile_texture, None));
-
-    let pso = factory.create_pipeline_simple(SHADER_VERT, SHADER_FRAG, tilemap_pipeline::new())
-      .expect("Terrain shader loading error");
+    TerrainDrawSystem {
+      slice: mesh.slice,
+      pso,
+      layers,
+      tile_sheets: terrain.tile_sets.iter().map(|tile_set| tile_set.image.clone()).collect(),
+      map: terrain.map.clone(),
+      is_tile_map_dirty: true,
+    }
+  }
 
-    let terrain = tile_map::Terrain::new();
+  pub fn map(&self) -> &str {
+    &self.map
+  }
 
-    let pipeline_data = tilemap_pipeline::Data {
-      vbuf: mesh.vertex_buffer,
-      position_cb: factory.create_constant_buffer(1),
-      time_passed_cb: factory.create_constant_buffer(1),
-      projection_cb: factory.create_constant_buffer(1),
-      tilemap: factory.create_buffer_immutable(&terrain.tiles.as_slice(),
-                                               gfx::buffer::Role::Constant,
-                                               gfx::memory::Bind::empty()).unwrap(),
-      tilemap_cb: factory.create_constant_buffer(1),
-      tilesheet: (mesh.texture.raw, factory.create_sampler_linear()),
-      out_color: rtv,
-      out_depth: dsv,
-    };
+  pub fn update_views(&mut self,
+                      rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+                      dsv: gfx::handle::DepthStencilView<R, DepthFormat>) {
+    for layer in &mut self.layers {
+      layer.data.set_views(rtv.clone(), dsv.clone());
+    }
+  }
 
-    TerrainDrawSystem {
-      bundle: gfx::Bundle::new(mesh.slice, pso, pipeline_data),
-      is_tile_map_dirty: true,
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, changed: &[String])
+    where F: gfx::Factory<R> {
+    if let Some(pso) = assets::reload(changed, &[SHADER_VERT, SHADER_FRAG], || load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, tilemap_pipeline::new())) {
+      self.pso = pso;
+    }
+    for (idx, tile_sheet) in self.tile_sheets.iter().enumerate() {
+      if let Some(texture) = assets::reload(changed, &[tile_sheet], || try_load_texture(factory, &assets.try_bytes(tile_sheet)?)) {
+        for layer in self.layers.iter_mut().filter(|layer| layer.tile_set == idx) {
+          layer.data.tilesheet.0 = texture.clone();
+        }
+      }
     }
   }
 
   pub fn draw<C>(&mut self,
                  drawable: &TerrainDrawable,
+                 projection: &Projection,
                  time_passed: u64,
                  encoder: &mut gfx::Encoder<R, C>)
     where C: gfx::CommandBuffer<R> {
-    encoder.update_constant_buffer(&self.bundle.data.projection_cb, &drawable.projection);
-    encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.position);
-    encoder.update_constant_buffer(&self.bundle.data.time_passed_cb, &Time::new(time_passed));
+    let first = match self.layers.first() {
+      Some(layer) => &layer.data,
+      None => return,
+    };
+    encoder.update_constant_buffer(&first.projection_cb, projection);
+    encoder.update_constant_buffer(&first.position_cb, &drawable.position);
+    encoder.update_constant_buffer(&first.time_passed_cb, &Time::new(time_passed));
 
     if self.is_tile_map_dirty {
-      encoder.update_constant_buffer(&self.bundle.data.tilemap_cb, &TilemapSettings {
-        world_size: [TILES_PCS_W as f32, TILES_PCS_H as f32],
-        tilesheet_size: [32.0, 32.0],
-      });
+      for layer in &self.layers {
+        encoder.update_constant_buffer(&layer.data.tilemap_cb, &layer.settings);
+      }
       self.is_tile_map_dirty = false
     }
 
-    self.bundle.encode(encoder);
+    for layer in &self.layers {
+      encoder.draw(&self.slice, &self.pso, &layer.data);
+    }
   }
 }
 
@@ -148,16 +217,14 @@ pub struct PreDrawSystem;
 
 impl<'a> specs::prelude::System<'a> for PreDrawSystem {
   type SystemData = (WriteStorage<'a, TerrainDrawable>,
-                     ReadStorage<'a, CameraInputState>,
                      WriteStorage<'a, CharacterInputState>,
-                     Read<'a, Dimensions>);
+                     ReadExpect<'a, CollisionGrid>);
 
-  fn run(&mut self, (mut terrain, camera_input, mut character_input, dim): Self::SystemData) {
+  fn run(&mut self, (mut terrain, mut character_input, collision): Self::SystemData) {
     use specs::join::Join;
 
-    for (t, camera, ci) in (&mut terrain, &camera_input, &mut character_input).join() {
-      let world_to_clip = dim.world_to_projection(camera);
-      t.update(&world_to_clip, ci);
+    for (t, ci) in (&mut terrain, &mut character_input).join() {
+      t.update(ci, &collision);
     }
   }
 }
@@ -1,12 +1,11 @@
 use cgmath::Point2;
 use pathfinding::{directed::astar::astar, utils::absdiff};
 
-use crate::game::constants::{TILES_PCS_H, TILES_PCS_W, TERRAIN_OBJECTS};
-use crate::game::get_rand_from_range;
-use crate::graphics::coords_to_tile;
+use crate::game::GameRng;
 use crate::shaders::Position;
+use crate::terrain::collision::CollisionGrid;
 
-fn neighbours<'c>(curr_pos: Point2<i32>, impassable_tiles: &[[i32; 2]], neighbour_tiles: &'c mut Vec<Point2<i32>>) -> Vec<&'c Point2<i32>> {
+fn neighbours<'c>(curr_pos: Point2<i32>, collision: &CollisionGrid, neighbour_tiles: &'c mut Vec<Point2<i32>>) -> Vec<&'c Point2<i32>> {
   neighbour_tiles.push(Point2::new(curr_pos.x - 1, curr_pos.y));
   neighbour_tiles.push(Point2::new(curr_pos.x - 1, curr_pos.y - 1));
   neighbour_tiles.push(Point2::new(curr_pos.x, curr_pos.y - 1));
@@ -18,41 +17,40 @@ fn neighbours<'c>(curr_pos: Point2<i32>, impassable_tiles: &[[i32; 2]], neighbou
 
   neighbour_tiles
     .iter()
-    .filter(|ref e| e.x >= 0 && e.x < TILES_PCS_W as i32 && e.y >= 0 && e.y < TILES_PCS_H as i32)
-    .filter(|ref e| !impassable_tiles.contains(&[e.x, e.y]))
+    .filter(|e| !collision.is_blocked(**e))
     .collect()
 }
 
-fn tiles(p: Point2<i32>, impassable_tiles: &[[i32; 2]]) -> Vec<(Point2<i32>, i32)> {
-  neighbours(p, &impassable_tiles, &mut vec![])
+fn tiles(p: Point2<i32>, collision: &CollisionGrid) -> Vec<(Point2<i32>, i32)> {
+  neighbours(p, collision, &mut vec![])
     .iter()
     .map(|p| (**p, 1))
     .collect()
 }
 
-fn find_next_best_endpoint<'c>(end_point: &'c Point2<i32>, impassable_tiles: &[[i32; 2]], neighbour_tiles: &'c mut Vec<Point2<i32>>) -> &'c Point2<i32> {
-  if impassable_tiles.iter().any(|e| e[0] == end_point.x && e[1] == end_point.y) {
-    neighbours(*end_point, &impassable_tiles, neighbour_tiles)[0]
+fn find_next_best_endpoint<'c>(end_point: &'c Point2<i32>, collision: &CollisionGrid, neighbour_tiles: &'c mut Vec<Point2<i32>>) -> &'c Point2<i32> {
+  if collision.is_blocked(*end_point) {
+    neighbours(*end_point, collision, neighbour_tiles).first().copied().unwrap_or(end_point)
   } else {
     &end_point
   }
 }
 
-pub fn calc_route(start_point: Position, end_point: Position, impassable_tiles: &[[i32; 2]]) -> Option<(Vec<Point2<i32>>, i32)> {
+pub fn calc_route(start_point: Position, end_point: Position, collision: &CollisionGrid) -> Option<(Vec<Point2<i32>>, i32)> {
   let mut neighbour_tiles = vec![];
-  let end_tile = coords_to_tile(end_point);
-  let start_tile = coords_to_tile(start_point);
+  let end_tile = collision.size().coords_to_tile(end_point);
+  let start_tile = collision.size().coords_to_tile(start_point);
 
-  let end = find_next_best_endpoint(&end_tile, &impassable_tiles, &mut neighbour_tiles);
+  let end = find_next_best_endpoint(&end_tile, collision, &mut neighbour_tiles);
 
   astar(&start_tile,
-        |p: &Point2<i32>| tiles(*p, &impassable_tiles),
+        |p: &Point2<i32>| tiles(*p, collision),
         |p: &Point2<i32>| absdiff(p.x, end.x) + absdiff(p.y, end.y),
         |p: &Point2<i32>| p.x == end.x && p.y == end.y)
 }
 
-pub fn calc_next_movement(start_point: Position, end_point: Position) -> i32 {
-  let next_step: Point2<i32> = calc_route(start_point, end_point, &TERRAIN_OBJECTS.to_vec())
+pub fn calc_next_movement(start_point: Position, end_point: Position, collision: &CollisionGrid, rng: &mut GameRng) -> i32 {
+  let next_step: Point2<i32> = calc_route(start_point, end_point, collision)
     .map_or_else(|| Point2::new(0, 0),
                  |(route, ..)| {
                    if route.len() > 1 {
@@ -62,7 +60,7 @@ pub fn calc_next_movement(start_point: Position, end_point: Position) -> i32 {
                    }
                  });
 
-  let start = coords_to_tile(start_point);
+  let start = collision.size().coords_to_tile(start_point);
   let diff: (i32, i32) = (next_step.x - start.x, next_step.y - start.y);
 
   match diff {
@@ -74,6 +72,6 @@ pub fn calc_next_movement(start_point: Position, end_point: Position) -> i32 {
     (-1, -1) => 90,
     (0, -1) => 45,
     (1, -1) => 0,
-    _ => get_rand_from_range(0, 359),
+    _ => rng.get_rand_from_range(0, 359),
   }
 }
@@ -0,0 +1,25 @@
+#[test]
+fn terrain_vertices_test() {
+  use crate::graphics::MapSize;
+  use crate::terrain::{terrain_vertices, world_to_mesh};
+
+  let size = MapSize::new(200, 100);
+  let vertices = terrain_vertices(&size);
+  let (a, b, c) = (&vertices[0], &vertices[1], &vertices[2]);
+
+  // Positions are interpolated linearly between the quad corners by their tile coordinates
+  let mesh_position = |x: f32, y: f32| {
+    let det = (b.uv[0] - a.uv[0]) * (c.uv[1] - a.uv[1]) - (c.uv[0] - a.uv[0]) * (b.uv[1] - a.uv[1]);
+    let s = ((x - a.uv[0]) * (c.uv[1] - a.uv[1]) - (c.uv[0] - a.uv[0]) * (y - a.uv[1])) / det;
+    let t = ((b.uv[0] - a.uv[0]) * (y - a.uv[1]) - (x - a.uv[0]) * (b.uv[1] - a.uv[1])) / det;
+    [a.pos[0] + s * (b.pos[0] - a.pos[0]) + t * (c.pos[0] - a.pos[0]),
+      a.pos[1] + s * (b.pos[1] - a.pos[1]) + t * (c.pos[1] - a.pos[1])]
+  };
+
+  for &(x, y) in &[(0, 0), (199, 0), (0, 99), (150, 20), (37, 80)] {
+    let drawn = mesh_position(x as f32 + 0.5, y as f32 + 0.5);
+    let expected = world_to_mesh(size.tile_to_coords(x, y));
+    assert!((drawn[0] - expected[0]).abs() < 0.5 && (drawn[1] - expected[1]).abs() < 0.5,
+            "Tile {}, {} should be drawn at {:?}, found {:?}", x, y, expected, drawn);
+  }
+}
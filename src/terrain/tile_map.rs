@@ -1,60 +1,114 @@
-use tiled::Map;
+use tiled::{Map, Tileset};
 
-use crate::data::{get_map_tile, load_map_file};
-use crate::game::constants::{MAP_FILE_PATH, TILES_PCS_H, TILES_PCS_W};
-use crate::shaders::TileMapData;
+use crate::data::get_map_tile;
+use crate::graphics::MapSize;
 
-const TILEMAP_BUF_LENGTH: usize = TILES_PCS_H * TILES_PCS_H;
-const QUARTER_BUF_LENGTH: usize = TILEMAP_BUF_LENGTH / 4;
+// Tile indices are uploaded as a texture, OpenGL 3.2 guarantees 1024 texels per side
+const MAX_MAP_SIZE: usize = 1024;
 
-fn calc_index(x_pos: usize, y_pos: usize) -> usize {
-  (y_pos * TILES_PCS_W) + x_pos
+// Tiled keeps the flip flags in the highest bits of a tile gid
+pub const TILE_GID_MASK: u32 = 0x1FFF_FFFF;
+
+// Tile sets own the gids from their first gid up to the next tile set
+pub fn find_tile_set(map: &Map, gid: u32) -> Option<(usize, &Tileset)> {
+  map.tilesets.iter().enumerate()
+    .filter(|(_, tile_set)| tile_set.first_gid <= gid)
+    .max_by_key(|(_, tile_set)| tile_set.first_gid)
 }
 
-fn populate_tile_map<'a>(tiles: &'a mut Vec<TileMapData>, map: &Map) -> &'a mut Vec<TileMapData> {
-  for y_pos in 0..TILES_PCS_H {
-    for x_pos in 0..TILES_PCS_W {
-      let map_val = get_map_tile(map, 0, x_pos, y_pos) - 1;
-      let idx = calc_index(x_pos, y_pos);
-
-      if idx < QUARTER_BUF_LENGTH {
-        tiles[idx] =
-          TileMapData::new([map_val as f32, 0.0, 0.0, 0.0]);
-      } else if idx < QUARTER_BUF_LENGTH * 2 {
-        tiles[idx - QUARTER_BUF_LENGTH] =
-          TileMapData::new([tiles[idx - QUARTER_BUF_LENGTH].data[0], map_val as f32, 0.0, 0.0]);
-      } else if idx < QUARTER_BUF_LENGTH * 3 {
-        tiles[idx - QUARTER_BUF_LENGTH * 2] =
-          TileMapData::new([tiles[idx - QUARTER_BUF_LENGTH * 2].data[0], tiles[idx - QUARTER_BUF_LENGTH * 2].data[1], map_val as f32, 0.0]);
-      } else {
-        tiles[idx - QUARTER_BUF_LENGTH * 3] =
-          TileMapData::new([tiles[idx - QUARTER_BUF_LENGTH * 3].data[0], tiles[idx - QUARTER_BUF_LENGTH * 3].data[1], tiles[idx - QUARTER_BUF_LENGTH * 3].data[2], map_val as f32]);
-      }
-    }
-  }
-  tiles
+pub struct TileSet {
+  pub image: String,
+  pub size: [f32; 2],
+}
+
+// Tiles of one map layer drawn from one tile set, row by row. Cells using other tile sets are left empty
+pub struct TileLayer {
+  pub tile_set: usize,
+  pub opacity: f32,
+  pub tiles: Vec<f32>,
 }
 
 pub struct Terrain {
-  pub tiles: Vec<TileMapData>,
-  pub tile_sets: [Map; 1],
-  pub curr_tile_set_idx: usize,
+  pub map: String,
+  pub size: MapSize,
+  pub tile_sets: Vec<TileSet>,
+  pub layers: Vec<TileLayer>,
 }
 
 impl Terrain {
-  pub fn new() -> Terrain {
-    let mut map_data = Vec::with_capacity(TILEMAP_BUF_LENGTH);
-
-    for _ in 0..TILEMAP_BUF_LENGTH {
-      map_data.push(TileMapData::new_empty());
+  // Visible tile layers in map order, tile set images are resolved relative to the map file
+  pub fn from_map(map: &Map, map_path: &str) -> Result<Terrain, String> {
+    let size = MapSize::new(map.width as usize, map.height as usize);
+    if size.width == 0 || size.height == 0 || size.width > MAX_MAP_SIZE || size.height > MAX_MAP_SIZE {
+      return Err(format!("Map size {}x{} should be between 1x1 and {}x{} tiles", size.width, size.height, MAX_MAP_SIZE, MAX_MAP_SIZE));
     }
+    let tile_sets = map.tilesets.iter()
+      .map(|tile_set| {
+        let image = tile_set.images.first()
+          .ok_or_else(|| format!("Tile set {} has no image", tile_set.name))?;
+        if tile_set.margin != 0 || tile_set.spacing != 0 {
+          return Err(format!("Tile set {} uses margin or spacing, which is not supported", tile_set.name));
+        }
+        let columns = image.width / tile_set.tile_width as i32;
+        let rows = image.height / tile_set.tile_height as i32;
+        if columns <= 0 || rows <= 0 {
+          return Err(format!("Tile set {} image is smaller than a tile", tile_set.name));
+        }
+        Ok(TileSet {
+          image: relative_asset(map_path, &image.source),
+          size: [columns as f32, rows as f32],
+        })
+      })
+      .collect::<Result<Vec<TileSet>, String>>()?;
 
-    let map_a = load_map_file(MAP_FILE_PATH);
+    let mut layers = Vec::new();
+    for (layer_idx, layer) in map.layers.iter().enumerate() {
+      if layer.tiles.len() != size.height || layer.tiles.iter().any(|row| row.len() != size.width) {
+        return Err(format!("Layer {} should be {}x{} tiles", layer.name, size.width, size.height));
+      }
+      if !layer.visible {
+        continue;
+      }
+
+      let mut layer_tiles: Vec<Option<Vec<f32>>> = (0..tile_sets.len()).map(|_| None).collect();
+      for y_pos in 0..size.height {
+        for x_pos in 0..size.width {
+          let gid = get_map_tile(map, layer_idx, x_pos, y_pos) & TILE_GID_MASK;
+          if gid == 0 {
+            continue;
+          }
+          let (set_idx, tile_set) = find_tile_set(map, gid)
+            .ok_or_else(|| format!("Tile {} in layer {} has no tile set", gid, layer.name))?;
+          let tile_idx = gid - tile_set.first_gid;
+          if tile_idx as f32 >= tile_sets[set_idx].size[0] * tile_sets[set_idx].size[1] {
+            return Err(format!("Tile {} in layer {} is outside of tile set {}", gid, layer.name, tile_set.name));
+          }
+          let tiles = layer_tiles[set_idx].get_or_insert_with(|| vec![-1.0; size.width * size.height]);
+          tiles[y_pos * size.width + x_pos] = tile_idx as f32;
+        }
+      }
 
-    Terrain {
-      tiles: populate_tile_map(&mut map_data, &map_a).to_vec(),
-      tile_sets: [map_a],
-      curr_tile_set_idx: 0,
+      layers.extend(layer_tiles.into_iter()
+        .enumerate()
+        .filter_map(|(tile_set, tiles)| tiles.map(|tiles| TileLayer {
+          tile_set,
+          opacity: layer.opacity,
+          tiles,
+        })));
     }
+
+    Ok(Terrain {
+      map: map_path.to_string(),
+      size,
+      tile_sets,
+      layers,
+    })
+  }
+}
+
+fn relative_asset(map_path: &str, source: &str) -> String {
+  match map_path.rfind('/') {
+    Some(idx) => format!("{}/{}", &map_path[..idx], source),
+    None => source.to_string(),
   }
 }
@@ -0,0 +1,61 @@
+#[test]
+fn tile_layers_test() {
+  use std::io::Cursor;
+
+  use crate::graphics::MapSize;
+  use crate::terrain::tile_map::Terrain;
+
+  let (width, height) = (6, 3);
+
+  let test_map = |overlay: &str| {
+    let ground = vec![vec!["1"; width].join(","); height].join(",\n");
+    let mut rows = vec![vec!["0"; width].join(","); height - 1];
+    rows.push(overlay.to_string());
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.2" orientation="isometric" renderorder="left-down" width="{w}" height="{h}" tilewidth="32" tileheight="32">
+ <tileset firstgid="1" name="ground" tilewidth="32" tileheight="32">
+  <image source="ground.png" width="64" height="64"/>
+ </tileset>
+ <tileset firstgid="5" name="roads" tilewidth="32" tileheight="32">
+  <image source="../roads/roads.png" width="64" height="32"/>
+ </tileset>
+ <layer name="ground" width="{w}" height="{h}">
+  <data encoding="csv">{ground}</data>
+ </layer>
+ <layer name="overlay" width="{w}" height="{h}" opacity="0.5">
+  <data encoding="csv">{overlay}</data>
+ </layer>
+</map>"#, w = width, h = height, ground = ground, overlay = rows.join(",\n"))
+  };
+
+  // Overlay cells use both tile sets, the flip flag of the last one is ignored
+  let mut overlay = vec!["0".to_string(); width];
+  overlay[1] = "2".to_string();
+  overlay[3] = "6".to_string();
+  overlay[4] = (5 | 0x8000_0000u32).to_string();
+  let map = tiled::parse(Cursor::new(test_map(&overlay.join(",")))).expect("Map should parse");
+  let terrain = Terrain::from_map(&map, "maps/test.tmx").expect("Terrain should load");
+
+  assert_eq!(terrain.size, MapSize::new(width, height));
+  assert_eq!(terrain.tile_sets[0].image, "maps/ground.png");
+  assert_eq!(terrain.tile_sets[1].image, "maps/../roads/roads.png");
+  assert_eq!(terrain.tile_sets[1].size, [2.0, 1.0]);
+
+  let layers = terrain.layers.iter().map(|l| (l.tile_set, l.opacity)).collect::<Vec<_>>();
+  assert_eq!(layers, [(0, 1.0), (0, 0.5), (1, 0.5)], "Overlay should be split by tile set and drawn after the ground");
+  // The bottom map row is the first one in the texture
+  let tiles = &terrain.layers[1].tiles;
+  assert_eq!(tiles.len(), width * height, "Layers should cover the whole map");
+  assert_eq!(terrain.layers[0].tiles[0], 0.0);
+  assert_eq!(tiles[0], -1.0, "Empty cells should be negative");
+  assert_eq!(tiles[1], 1.0);
+  assert_eq!(tiles[3], -1.0, "Cells of other tile sets should be empty");
+  assert_eq!(tiles[width + 1], -1.0);
+  assert_eq!(terrain.layers[2].tiles[3], 1.0);
+  assert_eq!(terrain.layers[2].tiles[4], 0.0);
+
+  overlay[0] = "7".to_string();
+  let map = tiled::parse(Cursor::new(test_map(&overlay.join(",")))).expect("Map should parse");
+  assert_eq!(Terrain::from_map(&map, "maps/test.tmx").err().expect("Terrain should not load"),
+             "Tile 7 in layer overlay is outside of tile set roads");
+}
@@ -1,47 +1,32 @@
 use cgmath::{Point2, Matrix2};
-use specs::{Read, ReadStorage, WriteStorage};
 
-use crate::character::controls::CharacterInputState;
-use crate::game::constants::{ASPECT_RATIO, VIEW_DISTANCE};
+use crate::assets;
+use crate::assets::AssetManager;
 use crate::gfx_app::{ColorFormat, DepthFormat};
-use crate::graphics::camera::CameraInputState;
-use crate::graphics::dimensions::{Dimensions, get_projection, get_view_matrix};
 use crate::graphics::mesh::{Geometry, RectangularTexturedMesh};
 use crate::graphics::orientation::Orientation;
-use crate::graphics::texture::{load_texture, Texture};
-use crate::shaders::{Position, Projection, static_element_pipeline, Time};
-use crate::terrain_shape::terrain_shape_objects::TerrainShapeObjects;
+use crate::graphics::texture::{load_texture, Texture, try_load_texture};
+use crate::shaders::{load_pipeline, Position, Projection, RenderViews, static_element_pipeline, Time};
 
 pub mod terrain_shape_objects;
 
-const SHADER_VERT: &[u8] = include_bytes!("../shaders/static_element.v.glsl");
-const SHADER_FRAG: &[u8] = include_bytes!("../shaders/static_element.f.glsl");
+const SHADER_VERT: &str = "shaders/static_element.v.glsl";
+const SHADER_FRAG: &str = "shaders/static_element.f.glsl";
+const SHAPE_TEXTURE: &str = "maps/shape.png";
 
 pub struct TerrainShapeDrawable {
-  projection: Projection,
   pub position: Position,
-  previous_position: Position,
   orientation: Orientation,
 }
 
 impl TerrainShapeDrawable {
   pub fn new(position: Position, orientation: Orientation) -> TerrainShapeDrawable {
-    let view = get_view_matrix(VIEW_DISTANCE);
-    let projection = get_projection(view, ASPECT_RATIO);
     TerrainShapeDrawable {
       position,
-      previous_position: Position::origin(),
-      projection,
       orientation,
     }
   }
 
-  pub fn update(&mut self, world_to_clip: &Projection, ci: &CharacterInputState) {
-    self.projection = *world_to_clip;
-    self.position = self.position + ci.movement - self.previous_position;
-    self.previous_position = ci.movement;
-  }
-
   pub fn get_shape(&self) -> &Orientation {
     &self.orientation
   }
@@ -57,6 +42,7 @@ pub struct TerrainShapeDrawSystem<R: gfx::Resources> {
 
 impl<R: gfx::Resources> TerrainShapeDrawSystem<R> {
   pub fn new<F>(factory: &mut F,
+                assets: &mut AssetManager,
                 rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
                 dsv: gfx::handle::DepthStencilView<R, DepthFormat>,
                 shape: Orientation,
@@ -64,8 +50,7 @@ impl<R: gfx::Resources> TerrainShapeDrawSystem<R> {
     where F: gfx::Factory<R> {
     use gfx::traits::FactoryExt;
 
-    let terrain_shape_bytes = include_bytes!("../../assets/maps/shape.png");
-    let terrain_shape_texture = load_texture(factory, terrain_shape_bytes);
+    let terrain_shape_texture = load_texture(factory, &assets.bytes(SHAPE_TEXTURE));
 
     let size = Point2::new(42.0, 42.0);
     let texture = Texture::new(terrain_shape_texture, None);
@@ -102,9 +87,8 @@ impl<R: gfx::Resources> TerrainShapeDrawSystem<R> {
       Orientation::Up =>  RectangularTexturedMesh::new(factory, texture, Geometry::Triangle, size, scale, rotation, Some(Orientation::Up)),
     };
 
-    let pso = factory
-      .create_pipeline_simple(SHADER_VERT, SHADER_FRAG, static_element_pipeline::new())
-      .expect("Terrain shape shader loading error");
+    let pso = load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, static_element_pipeline::new())
+      .unwrap_or_else(|e| panic!("Terrain shape shader loading error: {}", e));
 
     let pipeline_data = static_element_pipeline::Data {
       vbuf: rect_mesh.mesh.vertex_buffer,
@@ -121,35 +105,32 @@ impl<R: gfx::Resources> TerrainShapeDrawSystem<R> {
     }
   }
 
+  pub fn update_views(&mut self,
+                      rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+                      dsv: gfx::handle::DepthStencilView<R, DepthFormat>) {
+    self.bundle.data.set_views(rtv, dsv);
+  }
+
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, changed: &[String])
+    where F: gfx::Factory<R> {
+    if let Some(pso) = assets::reload(changed, &[SHADER_VERT, SHADER_FRAG], || load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, static_element_pipeline::new())) {
+      self.bundle.pso = pso;
+    }
+    let texture = SHAPE_TEXTURE;
+    if let Some(raw) = assets::reload(changed, &[texture], || try_load_texture(factory, &assets.try_bytes(texture)?)) {
+      self.bundle.data.static_element_sheet.0 = raw;
+    }
+  }
+
   pub fn draw<C>(&self,
                  drawable: &TerrainShapeDrawable,
+                 projection: &Projection,
                  time_passed: u64,
                  encoder: &mut gfx::Encoder<R, C>)
     where C: gfx::CommandBuffer<R> {
-    encoder.update_constant_buffer(&self.bundle.data.projection_cb, &drawable.projection);
+    encoder.update_constant_buffer(&self.bundle.data.projection_cb, projection);
     encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.position);
     encoder.update_constant_buffer(&self.bundle.data.time_passed_cb, &Time::new(time_passed));
     self.bundle.encode(encoder);
   }
 }
-
-pub struct PreDrawSystem;
-
-impl<'a> specs::prelude::System<'a> for PreDrawSystem {
-  type SystemData = (ReadStorage<'a, CameraInputState>,
-                     ReadStorage<'a, CharacterInputState>,
-                     WriteStorage<'a, TerrainShapeObjects>,
-                     Read<'a, Dimensions>);
-
-  fn run(&mut self, (camera_input, character_input, mut terrain_shape_objects, dim): Self::SystemData) {
-    use specs::join::Join;
-
-    for (camera, ci, ts_obj) in (&camera_input, &character_input, &mut terrain_shape_objects).join() {
-      let world_to_clip = dim.world_to_projection(camera);
-
-      for o in &mut ts_obj.objects {
-        o.update(&world_to_clip, ci);
-      }
-    }
-  }
-}
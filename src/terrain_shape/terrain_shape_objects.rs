@@ -2,6 +2,7 @@ use specs;
 
 use crate::graphics::orientation::Orientation;
 use crate::graphics::set_position;
+use crate::shaders::Position;
 use crate::terrain_shape::TerrainShapeDrawable;
 
 pub struct TerrainShapeObjects {
@@ -15,16 +16,13 @@ impl TerrainShapeObjects {
     }
   }
 
-  pub fn small_hill(&mut self, x: i32, y: i32) {
-    self.objects.push(TerrainShapeDrawable::new(set_position(x, y), Orientation::Normal));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x - 1, y - 1), Orientation::DownLeft));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x + 1, y - 1), Orientation::DownRight));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x - 1, y + 1), Orientation::UpLeft));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x + 1, y + 1), Orientation::UpRight));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x - 2, y), Orientation::Left));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x + 2, y), Orientation::Right));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x, y - 1), Orientation::Down));
-    self.objects.push(TerrainShapeDrawable::new(set_position(x, y + 1), Orientation::Up));
+  pub fn small_hill(&mut self, center: Position) {
+    let pieces = [(0, 0, Orientation::Normal), (-1, -1, Orientation::DownLeft), (1, -1, Orientation::DownRight),
+      (-1, 1, Orientation::UpLeft), (1, 1, Orientation::UpRight), (-2, 0, Orientation::Left),
+      (2, 0, Orientation::Right), (0, -1, Orientation::Down), (0, 1, Orientation::Up)];
+    for (x, y, orientation) in pieces.iter() {
+      self.objects.push(TerrainShapeDrawable::new(center + set_position(*x, *y), *orientation));
+    }
   }
 }
 
@@ -1,176 +1,251 @@
+use std::sync::Arc;
+
 use cgmath::Point2;
 use gfx;
 use specs;
-use specs::prelude::{Read, ReadStorage, WriteStorage};
+use specs::prelude::{Builder, Entities, Entity, ParJoin, ParallelIterator, Read, ReadExpect, ReadStorage, WriteStorage};
 
-use crate::bullet::{BulletDrawable, bullets::Bullets};
 use crate::character::controls::CharacterInputState;
-use crate::critter::CritterData;
-use crate::data;
-use crate::game::constants::{ASPECT_RATIO, NORMAL_DEATH_SPRITE_OFFSET, SMALL_HILLS, SPRITE_OFFSET, VIEW_DISTANCE, ZOMBIE_SHEET_TOTAL_WIDTH, ZOMBIE_STILL_SPRITE_OFFSET};
-use crate::game::get_random_bool;
+use crate::assets;
+use crate::assets::AssetManager;
+use crate::critter::CritterSprites;
+use crate::game::constants::{CORPSE_FADE_TIME, ZOMBIE_JSON_PATH, ZOMBIE_RUN_SPEED, ZOMBIE_WALK_SPEED};
+use crate::game::GameRng;
+use crate::game::settings::Settings;
 use crate::gfx_app::{ColorFormat, DepthFormat};
-use crate::graphics::{camera::CameraInputState, can_move_to_tile, check_terrain_elevation, direction, direction_movement, direction_movement_180, distance, GameTime, get_nearest_random_tile_position, orientation::{Orientation, Stance}, orientation_to_direction, overlaps};
-use crate::graphics::dimensions::{Dimensions, get_projection, get_view_matrix};
+use crate::graphics::animation::{AnimationSet, Animator};
+use crate::graphics::{can_move_to_tile, check_terrain_elevation, DeltaTime, direction, direction_movement, direction_movement_180, distance, GameTime, get_nearest_random_tile_position, orientation::{Orientation, Stance}, orientation_to_direction, spatial_grid::{SpatialGrid, SpatialIndex}};
 use crate::graphics::mesh::{Geometry, RectangularTexturedMesh};
-use crate::graphics::texture::{load_texture, Texture};
-use crate::shaders::{CharacterSheet, critter_pipeline, Position, Projection};
+use crate::graphics::texture::Texture;
+use crate::shaders::{CharacterSheet, critter_pipeline, load_pipeline, Position, Projection, RenderViews};
+use crate::terrain::collision::CollisionGrid;
 use crate::terrain::path_finding::calc_next_movement;
-use crate::zombie::zombies::Zombies;
 
-pub mod zombies;
+pub mod waves;
+mod waves_test;
+mod zombie_test;
 
-const SHADER_VERT: &[u8] = include_bytes!("../shaders/character.v.glsl");
-const SHADER_FRAG: &[u8] = include_bytes!("../shaders/character.f.glsl");
+const SHADER_VERT: &str = "shaders/character.v.glsl";
+const SHADER_FRAG: &str = "shaders/character.f.glsl";
 
-pub struct ZombieDrawable {
-  projection: Projection,
-  pub position: Position,
-  previous_position: Position,
-  previous_elevation: f32,
-  orientation: Orientation,
-  pub stance: Stance,
-  direction: Orientation,
-  last_decision: i64,
-  pub movement_direction: Point2<f32>,
-  zombie_idx: usize,
-  zombie_death_idx: usize,
-  movement_speed: f32,
-  health: f32,
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZombieKind {
+  pub health: f32,
+  pub speed: f32,
 }
 
-impl ZombieDrawable {
-  pub fn new(position: Position) -> ZombieDrawable {
-    let view = get_view_matrix(VIEW_DISTANCE);
-    let projection = get_projection(view, ASPECT_RATIO);
-    ZombieDrawable {
-      projection,
-      position,
-      previous_position: Position::origin(),
-      orientation: Orientation::Left,
-      stance: Stance::Still,
-      direction: Orientation::Left,
-      last_decision: -2,
-      movement_direction: Point2::new(0.0, 0.0),
-      previous_elevation: 0.0,
-      zombie_idx: 0,
-      zombie_death_idx: 0,
-      movement_speed: 0.0,
+impl ZombieKind {
+  pub fn new() -> ZombieKind {
+    ZombieKind {
       health: 1.0,
+      speed: 1.0,
     }
   }
+}
 
-  pub fn update(&mut self, world_to_clip: &Projection, ci: &CharacterInputState, game_time: u64) {
-    self.projection = *world_to_clip;
+impl Default for ZombieKind {
+  fn default() -> ZombieKind {
+    ZombieKind::new()
+  }
+}
 
-    let elevated_pos_y = check_terrain_elevation(ci.movement - self.position, &SMALL_HILLS);
+pub struct Velocity {
+  pub direction: Point2<f32>,
+  pub speed: f32,
+  speed_factor: f32,
+}
 
-    let offset_delta = ci.movement - self.previous_position;
-    self.previous_position = ci.movement;
+impl Velocity {
+  pub fn new(speed_factor: f32) -> Velocity {
+    Velocity {
+      direction: Point2::new(0.0, 0.0),
+      speed: 0.0,
+      speed_factor,
+    }
+  }
+}
 
-    let x_y_distance_to_player = self.position - offset_delta;
+impl specs::prelude::Component for Velocity {
+  type Storage = specs::storage::VecStorage<Velocity>;
+}
 
-    let distance_to_player = distance(x_y_distance_to_player.x().abs(), x_y_distance_to_player.y().abs());
+pub struct Health(pub f32);
 
-    let is_alive = self.health > 0.0 && self.stance != Stance::NormalDeath && self.stance != Stance::CriticalDeath;
+impl specs::prelude::Component for Health {
+  type Storage = specs::storage::VecStorage<Health>;
+}
 
-    if is_alive {
-      let zombie_pos = ci.movement - self.position;
+pub struct AiState {
+  pub stance: Stance,
+  last_decision: i64,
+  rng: GameRng,
+}
 
-      if distance_to_player < 400.0 {
-        let dir = calc_next_movement(zombie_pos, self.previous_position) as f32;
-        self.direction = orientation_to_direction(dir);
-        self.movement_direction = direction_movement(dir);
-        self.stance = Stance::Running;
-        self.movement_speed = 2.0 * self.health;
-      } else {
-        self.idle_direction_movement(zombie_pos, game_time as i64);
-        self.movement_speed = self.health;
-      }
-    } else {
-      self.movement_direction = Point2::new(0.0, 0.0);
+impl AiState {
+  pub fn new(rng: GameRng) -> AiState {
+    AiState {
+      stance: Stance::Still,
+      last_decision: -2,
+      rng,
     }
+  }
 
-    self.position = Position::new(self.position.position[0] + self.movement_direction.x * self.movement_speed,
-                                  self.position.position[1] + (elevated_pos_y - self.previous_elevation) + self.movement_direction.y * self.movement_speed) + offset_delta;
-    self.previous_elevation = elevated_pos_y;
-
+  pub fn is_alive(&self) -> bool {
+    self.stance != Stance::NormalDeath && self.stance != Stance::CriticalDeath
   }
 
-  fn idle_direction_movement(&mut self, zombie_pos: Position, game_time: i64) {
-    if !can_move_to_tile(zombie_pos) {
-      let dir = direction(self.movement_direction, Point2::new(0.0, 0.0));
-      self.movement_direction = direction_movement_180(self.movement_direction);
-      self.orientation = orientation_to_direction(dir);
-      self.direction = orientation_to_direction(dir);
+  fn idle_movement(&mut self, zombie_pos: Position, game_time: i64, collision: &CollisionGrid, velocity: &mut Velocity, animation: &mut SpriteAnimation) {
+    if !can_move_to_tile(collision, zombie_pos) {
+      let dir = direction(velocity.direction, Point2::new(0.0, 0.0));
+      velocity.direction = direction_movement_180(velocity.direction);
+      animation.direction = orientation_to_direction(dir);
     }
 
     if self.last_decision + 2 < game_time {
       self.stance = Stance::Walking;
       self.last_decision = game_time;
-      let end_point = get_nearest_random_tile_position(zombie_pos);
-      let dir = calc_next_movement(zombie_pos, end_point) as f32;
-      self.movement_direction = direction_movement(dir);
-      self.direction = orientation_to_direction(dir);
+      let end_point = get_nearest_random_tile_position(collision, zombie_pos, &mut self.rng);
+      let dir = calc_next_movement(zombie_pos, end_point, collision, &mut self.rng) as f32;
+      velocity.direction = direction_movement(dir);
+      animation.direction = orientation_to_direction(dir);
     }
   }
 
-  fn handle_bullet_hit(&mut self) {
-    self.health -= 0.5;
-    if self.health <= 0.0 {
-      self.stance =
-        if get_random_bool() {
-          Stance::NormalDeath
-        } else {
-          Stance::CriticalDeath
-        };
+  fn check_bullet_hits(&mut self, position: Position, health: &mut Health, bullets: &SpatialGrid<usize>) {
+    for _ in bullets.query_aabb(position, 15.0, 15.0) {
+      if self.is_alive() {
+        health.0 -= 0.5;
+        if health.0 <= 0.0 {
+          self.stance =
+            if self.rng.get_random_bool() {
+              Stance::NormalDeath
+            } else {
+              Stance::CriticalDeath
+            };
+        }
+      }
     }
   }
+}
 
-  fn check_bullet_hits(&mut self, bullets: &[BulletDrawable]) {
-    bullets.iter().for_each(|bullet| {
-      if overlaps(self.position, bullet.position, 15.0, 15.0) && self.stance != Stance::NormalDeath && self.stance != Stance::CriticalDeath {
-        self.handle_bullet_hit()
-      }
-    });
+impl specs::prelude::Component for AiState {
+  type Storage = specs::storage::VecStorage<AiState>;
+}
+
+pub struct SpriteAnimation {
+  direction: Orientation,
+}
+
+impl SpriteAnimation {
+  pub fn new() -> SpriteAnimation {
+    SpriteAnimation {
+      direction: Orientation::Left,
+    }
   }
+}
 
-  pub fn update_alive_idx(&mut self, max_idx: usize) {
-    if self.zombie_idx < max_idx {
-      self.zombie_idx += 1;
-    } else {
-      self.zombie_idx = 0;
+impl specs::prelude::Component for SpriteAnimation {
+  type Storage = specs::storage::VecStorage<SpriteAnimation>;
+}
+
+pub struct Renderable {
+  pub position: Position,
+  last_position: Position,
+  pub alpha: f32,
+}
+
+impl Renderable {
+  pub fn new(position: Position) -> Renderable {
+    Renderable {
+      position,
+      last_position: position,
+      alpha: 1.0,
     }
   }
+}
+
+impl specs::prelude::Component for Renderable {
+  type Storage = specs::storage::VecStorage<Renderable>;
+}
+
+// Corpses start fading once the death clip fires its fall event
+pub struct Corpse {
+  elapsed: f32,
+  fallen: bool,
+}
 
-  pub fn update_death_idx(&mut self, max_idx: usize) {
-    if self.zombie_death_idx < max_idx {
-      self.zombie_death_idx += 1;
+impl Corpse {
+  pub fn new() -> Corpse {
+    Corpse {
+      elapsed: 0.0,
+      fallen: false,
     }
   }
+
+  pub fn alpha(&self, corpse_time: f32) -> f32 {
+    (1.0 - (self.elapsed - corpse_time) / CORPSE_FADE_TIME).clamp(0.0, 1.0)
+  }
+
+  pub fn is_expired(&self, corpse_time: f32) -> bool {
+    self.elapsed >= corpse_time + CORPSE_FADE_TIME
+  }
+}
+
+impl specs::prelude::Component for Corpse {
+  type Storage = specs::storage::DenseVecStorage<Corpse>;
+}
+
+fn stance_clip(stance: &Stance) -> &'static str {
+  match stance {
+    Stance::Walking => "walk",
+    Stance::Running => "run",
+    Stance::NormalDeath => "normal",
+    Stance::CriticalDeath => "critical",
+    _ => "still",
+  }
+}
+
+pub fn build_zombie<B: Builder>(builder: B, position: Position, kind: &ZombieKind, animations: &Arc<AnimationSet>, rng: GameRng) -> Entity {
+  builder
+    .with(position)
+    .with(Velocity::new(kind.speed))
+    .with(Health(kind.health))
+    .with(AiState::new(rng))
+    .with(SpriteAnimation::new())
+    .with(Animator::new(Arc::clone(animations), "still"))
+    .with(Renderable::new(position))
+    .build()
+}
+
+pub struct ZombieDrawable<'a> {
+  pub renderable: &'a Renderable,
+  pub animation: &'a SpriteAnimation,
+  pub animator: &'a Animator,
 }
 
 pub struct ZombieDrawSystem<R: gfx::Resources> {
   bundle: gfx::pso::bundle::Bundle<R, critter_pipeline::Data<R>>,
-  data: Vec<CritterData>,
+  sprites: CritterSprites<R>,
+  size: [f32; 2],
 }
 
 impl<R: gfx::Resources> ZombieDrawSystem<R> {
   pub fn new<F>(factory: &mut F,
+                assets: &mut AssetManager,
                 rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
                 dsv: gfx::handle::DepthStencilView<R, DepthFormat>) -> ZombieDrawSystem<R>
     where F: gfx::Factory<R> {
     use gfx::traits::FactoryExt;
 
-    let zombie_bytes = include_bytes!("../../assets/zombie.png");
-    let char_texture = load_texture(factory, zombie_bytes);
+    let sprites = CritterSprites::new(factory, assets, ZOMBIE_JSON_PATH);
+    let size = Point2::new(25.0, 35.0);
 
     let rect_mesh =
-      RectangularTexturedMesh::new(factory, Texture::new(char_texture, None), Geometry::Rectangle, Point2::new(25.0, 35.0), None, None, None);
+      RectangularTexturedMesh::new(factory, Texture::new(sprites.pages[0].clone(), None), Geometry::Rectangle, size, None, None, None);
 
     let pso =
-      factory.create_pipeline_simple(SHADER_VERT, SHADER_FRAG, critter_pipeline::new())
-        .expect("Zombie shader loading error");
+      load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, critter_pipeline::new())
+        .unwrap_or_else(|e| panic!("Zombie shader loading error: {}", e));
 
     let pipeline_data = critter_pipeline::Data {
       vbuf: rect_mesh.mesh.vertex_buffer,
@@ -182,61 +257,42 @@ impl<R: gfx::Resources> ZombieDrawSystem<R> {
       out_depth: dsv,
     };
 
-    let data = data::load_zombie();
-
     ZombieDrawSystem {
       bundle: gfx::Bundle::new(rect_mesh.mesh.slice, pso, pipeline_data),
-      data,
+      sprites,
+      size: [size.x, size.y],
     }
   }
 
-  fn get_next_sprite(&self, drawable: &mut ZombieDrawable) -> CharacterSheet {
-    let sprite_idx = match drawable.stance {
-      Stance::Still => {
-        (drawable.direction as usize * 4 + drawable.zombie_idx)
-      }
-      Stance::Walking if drawable.orientation != Orientation::Normal => {
-        (drawable.direction as usize * 8 + drawable.zombie_idx + ZOMBIE_STILL_SPRITE_OFFSET)
-      }
-      Stance::Running if drawable.orientation != Orientation::Normal => {
-        (drawable.direction as usize * 8 + drawable.zombie_idx + ZOMBIE_STILL_SPRITE_OFFSET)
-      }
-      Stance::NormalDeath if drawable.orientation != Orientation::Normal => {
-        (drawable.direction as usize * 6 + drawable.zombie_death_idx + NORMAL_DEATH_SPRITE_OFFSET)
-      }
-      Stance::CriticalDeath if drawable.orientation != Orientation::Normal => {
-        (drawable.direction as usize * 8 + drawable.zombie_death_idx)
-      }
-      _ => {
-        drawable.direction = drawable.orientation;
-        (drawable.orientation as usize * 8 + drawable.zombie_idx + ZOMBIE_STILL_SPRITE_OFFSET)
-      }
-    } as usize;
-
-    let (y_div, row_idx) =
-      if drawable.stance == Stance::NormalDeath || drawable.stance == Stance::CriticalDeath {
-        (0.0, 2)
-      } else {
-        (1.0, 2)
-      };
-
-    let elements_x = ZOMBIE_SHEET_TOTAL_WIDTH / (self.data[sprite_idx].data[2] + SPRITE_OFFSET);
-    CharacterSheet {
-      x_div: elements_x,
-      y_div,
-      row_idx,
-      index: sprite_idx as f32,
+  pub fn update_views(&mut self,
+                      rtv: gfx::handle::RenderTargetView<R, ColorFormat>,
+                      dsv: gfx::handle::DepthStencilView<R, DepthFormat>) {
+    self.bundle.data.set_views(rtv, dsv);
+  }
+
+  pub fn reload<F>(&mut self, factory: &mut F, assets: &mut AssetManager, animations: &AnimationSet, changed: &[String])
+    where F: gfx::Factory<R> {
+    if let Some(pso) = assets::reload(changed, &[SHADER_VERT, SHADER_FRAG], || load_pipeline(factory, assets, SHADER_VERT, SHADER_FRAG, critter_pipeline::new())) {
+      self.bundle.pso = pso;
     }
+    self.sprites.reload(factory, assets, animations, changed);
   }
 
   pub fn draw<C>(&mut self,
-                 mut drawable: &mut ZombieDrawable,
+                 drawable: &ZombieDrawable,
+                 projection: &Projection,
+                 alpha: f32,
                  encoder: &mut gfx::Encoder<R, C>)
     where C: gfx::CommandBuffer<R> {
-    encoder.update_constant_buffer(&self.bundle.data.projection_cb, &drawable.projection);
-    encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.position);
-    encoder.update_constant_buffer(&self.bundle.data.character_sprite_cb,
-                                   &self.get_next_sprite(&mut drawable));
+    encoder.update_constant_buffer(&self.bundle.data.projection_cb, projection);
+    encoder.update_constant_buffer(&self.bundle.data.position_cb, &drawable.renderable.last_position.lerp(drawable.renderable.position, alpha));
+    let frame = self.sprites.sheet.sprite(drawable.animator.sprites(), drawable.animation.direction, drawable.animator.frame());
+    let sprite = CharacterSheet {
+      alpha: drawable.renderable.alpha,
+      ..frame.sheet(self.size)
+    };
+    self.bundle.data.charactersheet.0 = self.sprites.pages[frame.page].clone();
+    encoder.update_constant_buffer(&self.bundle.data.character_sprite_cb, &sprite);
     self.bundle.encode(encoder);
   }
 }
@@ -244,22 +300,97 @@ impl<R: gfx::Resources> ZombieDrawSystem<R> {
 pub struct PreDrawSystem;
 
 impl<'a> specs::prelude::System<'a> for PreDrawSystem {
-  type SystemData = (WriteStorage<'a, Zombies>,
-                     ReadStorage<'a, CameraInputState>,
+  type SystemData = (WriteStorage<'a, Position>,
+                     WriteStorage<'a, Velocity>,
+                     WriteStorage<'a, Health>,
+                     WriteStorage<'a, AiState>,
+                     WriteStorage<'a, SpriteAnimation>,
+                     WriteStorage<'a, Animator>,
+                     WriteStorage<'a, Renderable>,
+                     ReadStorage<'a, Corpse>,
                      ReadStorage<'a, CharacterInputState>,
-                     ReadStorage<'a, Bullets>,
-                     Read<'a, Dimensions>,
-                     Read<'a, GameTime>);
+                     Read<'a, SpatialIndex>,
+                     ReadExpect<'a, CollisionGrid>,
+                     Read<'a, GameTime>,
+                     Read<'a, DeltaTime>);
+
+  fn run(&mut self, (mut positions, mut velocities, mut healths, mut ai_states, mut animations, mut animators, mut renderables, corpses, character_input, index, collision, gt, dt): Self::SystemData) {
+    use specs::join::Join;
+
+    let delta = dt.0 as f32;
+    let game_time = gt.0 as i64;
+
+    for ci in (&character_input).join() {
+      let player = ci.movement;
+
+      (&mut positions, &mut velocities, &mut healths, &mut ai_states, &mut animations, &mut animators, &mut renderables, !&corpses).par_join()
+        .for_each(|(position, velocity, health, ai, animation, animator, renderable, _)| {
+          let x_y_distance_to_player = player - *position;
+
+          let distance_to_player = distance(x_y_distance_to_player.x().abs(), x_y_distance_to_player.y().abs());
+
+          if health.0 > 0.0 && ai.is_alive() {
+            let zombie_pos = *position;
 
-  fn run(&mut self, (mut zombies, camera_input, character_input, bullets, dim, gt): Self::SystemData) {
+            if distance_to_player < 400.0 {
+              let dir = calc_next_movement(zombie_pos, player, &collision, &mut ai.rng) as f32;
+              animation.direction = orientation_to_direction(dir);
+              velocity.direction = direction_movement(dir);
+              ai.stance = Stance::Running;
+              velocity.speed = ZOMBIE_RUN_SPEED * velocity.speed_factor * health.0.min(1.0);
+            } else {
+              ai.idle_movement(zombie_pos, game_time, &collision, velocity, animation);
+              velocity.speed = ZOMBIE_WALK_SPEED * velocity.speed_factor * health.0.min(1.0);
+            }
+          } else {
+            velocity.direction = Point2::new(0.0, 0.0);
+          }
+
+          let step = velocity.speed * delta;
+          *position = *position - Position::new(velocity.direction.x * step, velocity.direction.y * step);
+
+          renderable.last_position = renderable.position;
+          renderable.position = *position - Position::new(0.0, check_terrain_elevation(*position, &index.hills));
+
+          ai.check_bullet_hits(renderable.position, health, &index.bullets);
+          animator.play(stance_clip(&ai.stance));
+        });
+    }
+  }
+}
+
+pub struct CorpseSystem;
+
+impl<'a> specs::prelude::System<'a> for CorpseSystem {
+  type SystemData = (Entities<'a>,
+                     ReadStorage<'a, AiState>,
+                     ReadStorage<'a, Animator>,
+                     WriteStorage<'a, Corpse>,
+                     WriteStorage<'a, Renderable>,
+                     Read<'a, Settings>,
+                     Read<'a, DeltaTime>);
+
+  fn run(&mut self, (entities, ai_states, animators, mut corpses, mut renderables, settings, dt): Self::SystemData) {
     use specs::join::Join;
 
-    for (zs, camera, ci, bs) in (&mut zombies, &camera_input, &character_input, &bullets).join() {
-      let world_to_clip = dim.world_to_projection(camera);
+    let died = (&entities, &ai_states, !&corpses).join()
+      .filter(|(_, ai, _)| !ai.is_alive())
+      .map(|(entity, _, _)| entity)
+      .collect::<Vec<Entity>>();
+    for entity in died {
+      corpses.insert(entity, Corpse::new()).expect("Corpse insert error");
+    }
 
-      for z in &mut zs.zombies {
-        z.update(&world_to_clip, ci, gt.0);
-        z.check_bullet_hits(&bs.bullets);
+    for (entity, corpse, animator, renderable) in (&entities, &mut corpses, &animators, &mut renderables).join() {
+      corpse.fallen |= animator.events().iter().any(|event| event == "fall");
+      if !corpse.fallen {
+        continue;
+      }
+      corpse.elapsed += dt.0 as f32;
+      renderable.last_position = renderable.position;
+      renderable.alpha = corpse.alpha(settings.corpse_time);
+      if corpse.is_expired(settings.corpse_time) {
+        entities.delete(entity).expect("Corpse delete error");
       }
     }
   }
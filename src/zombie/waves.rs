@@ -0,0 +1,272 @@
+use json;
+use specs;
+use specs::prelude::{Entities, LazyUpdate, Read, ReadExpect, ReadStorage, Write, WriteExpect};
+
+use crate::assets::AssetManager;
+use crate::character::controls::CharacterInputState;
+use crate::critter::CritterAnimations;
+use crate::data::read_non_negative;
+use crate::game::constants::{TILE_SIZE, ZOMBIE_SPAWN_ATTEMPTS};
+use crate::game::GameRng;
+use crate::graphics::{can_move_to_tile, camera::CameraInputState, DeltaTime};
+use crate::shaders::Position;
+use crate::terrain::collision::CollisionGrid;
+use crate::terrain::map_objects::{MapObjects, ZombieSpawn};
+use crate::zombie::{AiState, build_zombie, ZombieKind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnRule {
+  pub min_distance: f32,
+  pub max_distance: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaveDefinition {
+  pub count: usize,
+  pub delay: f64,
+  pub composition: Vec<(ZombieKind, u32)>,
+  pub spawn: SpawnRule,
+}
+
+impl WaveDefinition {
+  pub fn pick_kind(&self, rng: &mut GameRng) -> ZombieKind {
+    let total = self.composition.iter().map(|(_, weight)| weight).sum::<u32>();
+    let mut roll = rng.get_rand_from_range(0, total);
+    for (kind, weight) in &self.composition {
+      if roll < *weight {
+        return kind.clone();
+      }
+      roll -= weight;
+    }
+    unreachable!("Wave composition roll out of range")
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ramp {
+  pub count: f32,
+  pub health: f32,
+  pub speed: f32,
+}
+
+// Waves past the last definition repeat it, every wave scales by the ramp
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaveDefinitions {
+  waves: Vec<WaveDefinition>,
+  ramp: Ramp,
+}
+
+impl WaveDefinitions {
+  pub fn new() -> WaveDefinitions {
+    WaveDefinitions {
+      waves: vec![WaveDefinition {
+        count: 48,
+        delay: 0.0,
+        composition: vec![(ZombieKind::new(), 1)],
+        spawn: SpawnRule {
+          min_distance: 700.0,
+          max_distance: 1200.0,
+        },
+      }],
+      ramp: Ramp {
+        count: 0.25,
+        health: 0.1,
+        speed: 0.05,
+      },
+    }
+  }
+
+  pub fn load(assets: &mut AssetManager, path: &str) -> WaveDefinitions {
+    if !assets.exists(path) {
+      return WaveDefinitions::new();
+    }
+    match WaveDefinitions::parse(&assets.text(path)) {
+      Ok(w) => w,
+      Err(e) => panic!("Waves file {} error: {}", path, e),
+    }
+  }
+
+  pub fn parse(content: &str) -> Result<WaveDefinitions, String> {
+    let data = json::parse(content).map_err(|e| e.to_string())?;
+
+    let kinds = &data["kinds"];
+    if !kinds.is_object() {
+      return Err("kinds should be an object of zombie kinds".to_string());
+    }
+    if !data["waves"].is_array() || data["waves"].is_empty() {
+      return Err("waves should be a non-empty list".to_string());
+    }
+
+    let mut waves = Vec::new();
+    for (idx, wave) in data["waves"].members().enumerate() {
+      let mut composition = Vec::new();
+      for (name, weight) in wave["composition"].entries() {
+        let kind = &kinds[name];
+        if !kind.is_object() {
+          return Err(format!("Unknown zombie kind {} in wave {}", name, idx + 1));
+        }
+        let weight = weight.as_u32().filter(|w| *w > 0)
+          .ok_or_else(|| format!("Invalid weight for {} in wave {}: {}", name, idx + 1, weight))?;
+        composition.push((ZombieKind {
+          health: read_non_negative(kind, "health", name)?,
+          speed: read_non_negative(kind, "speed", name)?,
+        }, weight));
+      }
+      if composition.is_empty() {
+        return Err(format!("Wave {} composition should not be empty", idx + 1));
+      }
+
+      let definition = WaveDefinition {
+        count: wave["count"].as_usize().filter(|c| *c > 0)
+          .ok_or_else(|| format!("Invalid count in wave {}: {}", idx + 1, wave["count"]))?,
+        delay: wave["delay"].as_f64().filter(|d| *d >= 0.0)
+          .ok_or_else(|| format!("Invalid delay in wave {}: {}", idx + 1, wave["delay"]))?,
+        composition,
+        spawn: SpawnRule {
+          min_distance: read_non_negative(&wave["spawn"], "min_distance", "spawn")?,
+          max_distance: read_non_negative(&wave["spawn"], "max_distance", "spawn")?,
+        },
+      };
+      if definition.spawn.min_distance > definition.spawn.max_distance {
+        return Err(format!("Wave {} min_distance should not be greater than max_distance", idx + 1));
+      }
+      waves.push(definition);
+    }
+
+    let ramp = &data["ramp"];
+    Ok(WaveDefinitions {
+      waves,
+      ramp: Ramp {
+        count: read_non_negative(ramp, "count", "ramp")?,
+        health: read_non_negative(ramp, "health", "ramp")?,
+        speed: read_non_negative(ramp, "speed", "ramp")?,
+      },
+    })
+  }
+
+  pub fn wave(&self, number: usize) -> WaveDefinition {
+    let mut wave = self.waves[number.min(self.waves.len() - 1)].clone();
+    let ramp = |rate: f32| 1.0 + rate * number as f32;
+    wave.count = (wave.count as f32 * ramp(self.ramp.count)).round() as usize;
+    for (kind, _) in &mut wave.composition {
+      kind.health *= ramp(self.ramp.health);
+      kind.speed *= ramp(self.ramp.speed);
+    }
+    wave
+  }
+}
+
+impl Default for WaveDefinitions {
+  fn default() -> WaveDefinitions {
+    WaveDefinitions::new()
+  }
+}
+
+pub struct WaveSpawner {
+  definitions: WaveDefinitions,
+  pub wave: usize,
+  current: Option<WaveDefinition>,
+  pending: usize,
+  cleared: usize,
+  countdown: f64,
+}
+
+impl WaveSpawner {
+  pub fn new(definitions: WaveDefinitions) -> WaveSpawner {
+    let countdown = definitions.wave(0).delay;
+    WaveSpawner {
+      definitions,
+      wave: 0,
+      current: None,
+      pending: 0,
+      cleared: 0,
+      countdown,
+    }
+  }
+
+  pub fn update(&mut self, delta: f64, zombies_alive: bool) {
+    if self.current.is_some() {
+      if self.pending > 0 || zombies_alive {
+        return;
+      }
+      self.current = None;
+      self.cleared += 1;
+      self.countdown = self.definitions.wave(self.wave).delay;
+    }
+    self.countdown -= delta;
+    if self.countdown <= 0.0 {
+      let wave = self.definitions.wave(self.wave);
+      self.pending = wave.count;
+      self.current = Some(wave);
+      self.wave += 1;
+    }
+  }
+
+  pub fn pending(&self) -> usize {
+    self.pending
+  }
+
+  pub fn cleared(&self) -> usize {
+    self.cleared
+  }
+
+  pub fn spawned(&mut self) {
+    self.pending -= 1;
+  }
+}
+
+// Maps with spawn points spawn around them, otherwise around the player at the wave distance
+fn spawn_position(player: Position, camera: &CameraInputState, rule: &SpawnRule, spawns: &[ZombieSpawn], collision: &CollisionGrid, rng: &mut GameRng) -> Option<Position> {
+  (0..ZOMBIE_SPAWN_ATTEMPTS)
+    .map(|_| {
+      let angle = (rng.get_rand_from_range(0, 360) as f32).to_radians();
+      if spawns.is_empty() {
+        let distance = rng.get_rand_from_range(rule.min_distance as i32, rule.max_distance as i32 + 1) as f32;
+        player + Position::new(angle.cos() * distance, angle.sin() * distance)
+      } else {
+        let spawn = &spawns[rng.get_rand_from_range(0, spawns.len() as u32) as usize];
+        let distance = rng.get_rand_from_range(0, (spawn.radius * TILE_SIZE) as i32 + 1) as f32;
+        spawn.position + Position::new(angle.cos() * distance, angle.sin() * distance)
+      }
+    })
+    .find(|position| can_move_to_tile(collision, *position) && !camera.is_in_view(*position))
+}
+
+pub struct WaveSpawnerSystem;
+
+impl<'a> specs::prelude::System<'a> for WaveSpawnerSystem {
+  type SystemData = (WriteExpect<'a, WaveSpawner>,
+                     Write<'a, GameRng>,
+                     Entities<'a>,
+                     ReadStorage<'a, AiState>,
+                     ReadStorage<'a, CharacterInputState>,
+                     ReadStorage<'a, CameraInputState>,
+                     ReadExpect<'a, CritterAnimations>,
+                     ReadExpect<'a, MapObjects>,
+                     ReadExpect<'a, CollisionGrid>,
+                     Read<'a, DeltaTime>,
+                     Read<'a, LazyUpdate>);
+
+  fn run(&mut self, (mut spawner, mut rng, entities, ai_states, character_input, camera_input, animations, map_objects, collision, dt, lazy): Self::SystemData) {
+    use specs::join::Join;
+
+    spawner.update(dt.0, ai_states.join().any(|ai| ai.is_alive()));
+
+    let wave = match spawner.current {
+      Some(ref wave) if spawner.pending() > 0 => wave.clone(),
+      _ => return,
+    };
+    for (ci, camera) in (&character_input, &camera_input).join() {
+      while spawner.pending() > 0 {
+        match spawn_position(ci.movement, camera, &wave.spawn, &map_objects.zombie_spawns, &collision, &mut rng) {
+          Some(position) => {
+            let kind = wave.pick_kind(&mut rng);
+            build_zombie(lazy.create_entity(&entities), position, &kind, &animations.zombie, GameRng::new(rng.next_seed()));
+            spawner.spawned();
+          }
+          None => break,
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,53 @@
+#[test]
+fn waves_parse_test() {
+  use crate::assets::AssetManager;
+  use crate::game::constants::WAVES_JSON_PATH;
+  use crate::zombie::waves::WaveDefinitions;
+
+  let waves = WaveDefinitions::parse(r#"{
+    "kinds": { "walker": { "health": 1.0, "speed": 1.0 }, "runner": { "health": 0.5, "speed": 2.0 } },
+    "ramp": { "count": 0.5, "health": 0.1, "speed": 0.0 },
+    "waves": [
+      { "count": 10, "delay": 0, "composition": { "walker": 1 }, "spawn": { "min_distance": 500, "max_distance": 900 } },
+      { "count": 20, "delay": 5, "composition": { "walker": 1, "runner": 1 }, "spawn": { "min_distance": 500, "max_distance": 900 } }
+    ]
+  }"#).expect("Waves should parse");
+
+  assert_eq!(waves.wave(0).count, 10);
+  assert_eq!(waves.wave(1).count, 30, "Second wave should ramp its count");
+  assert_eq!(waves.wave(1).delay, 5.0);
+  assert_eq!(waves.wave(1).composition[1].0.speed, 2.0);
+  assert_eq!(waves.wave(3).count, 50, "Waves past the last definition should repeat it with ramp");
+  assert!((waves.wave(3).composition[0].0.health - 1.3).abs() < 1e-6);
+
+  assert!(WaveDefinitions::parse(r#"{ "kinds": {}, "ramp": { "count": 0, "health": 0, "speed": 0 }, "waves": [] }"#).is_err());
+  assert!(WaveDefinitions::parse(r#"{ "kinds": {}, "ramp": { "count": 0, "health": 0, "speed": 0 },
+    "waves": [{ "count": 1, "delay": 0, "composition": { "walker": 1 }, "spawn": { "min_distance": 0, "max_distance": 1 } }] }"#).is_err());
+
+  WaveDefinitions::load(&mut AssetManager::locate(None), WAVES_JSON_PATH);
+}
+
+#[test]
+fn wave_spawner_test() {
+  use crate::zombie::waves::{WaveDefinitions, WaveSpawner};
+
+  let mut spawner = WaveSpawner::new(WaveDefinitions::new());
+  spawner.update(0.1, false);
+  assert_eq!(spawner.wave, 1, "First wave should start without delay");
+  assert_eq!(spawner.pending(), 48);
+
+  spawner.update(0.1, false);
+  assert_eq!(spawner.wave, 1, "Wave should not advance while spawning");
+
+  for _ in 0..48 {
+    spawner.spawned();
+  }
+  spawner.update(0.1, true);
+  assert_eq!(spawner.wave, 1, "Wave should not advance while zombies are alive");
+  assert_eq!(spawner.cleared(), 0);
+
+  spawner.update(0.1, false);
+  assert_eq!(spawner.wave, 2, "Cleared wave should start the next one");
+  assert_eq!(spawner.cleared(), 1);
+  assert_eq!(spawner.pending(), 60, "Next wave should ramp up");
+}
@@ -0,0 +1,50 @@
+#[test]
+fn corpse_despawn_test() {
+  use specs::{Join, RunNow, world::WorldExt};
+
+  use crate::assets::AssetManager;
+  use crate::critter::CritterAnimations;
+  use crate::game::constants::{CORPSE_FADE_TIME, RESOLUTION_X, RESOLUTION_Y};
+  use crate::game::GameRng;
+  use crate::game::settings::Settings;
+  use crate::gfx_app::init::setup_world;
+  use crate::graphics::{DeltaTime, dimensions::Dimensions, orientation::Stance};
+  use crate::graphics::animation::{AnimationSystem, Animator};
+  use crate::shaders::Position;
+  use crate::zombie::{AiState, build_zombie, Corpse, CorpseSystem, Renderable, ZombieKind};
+
+  let mut world = specs::World::new();
+  world.insert(AssetManager::locate(None));
+  setup_world(&mut world, Dimensions::new(RESOLUTION_X as f32, RESOLUTION_Y as f32, 1.0), 1);
+  *world.write_resource::<DeltaTime>() = DeltaTime(0.5);
+  let corpse_time = world.read_resource::<Settings>().corpse_time;
+  let animations = world.read_resource::<CritterAnimations>().zombie.clone();
+
+  let zombie = build_zombie(world.create_entity(), Position::new(500.0, 500.0), &ZombieKind::new(), &animations, GameRng::new(1));
+  let alive = build_zombie(world.create_entity(), Position::new(-500.0, 500.0), &ZombieKind::new(), &animations, GameRng::new(2));
+  world.write_storage::<AiState>().get_mut(zombie).expect("Zombie should exist").stance = Stance::NormalDeath;
+
+  let ticks = ((corpse_time + CORPSE_FADE_TIME) / 0.5) as usize;
+  for _ in 0..ticks {
+    CorpseSystem.run_now(&world);
+    world.maintain();
+  }
+  assert!(world.is_alive(zombie), "Corpse should not fade before it has fallen");
+
+  world.write_storage::<Animator>().get_mut(zombie).expect("Zombie should be animated").play("normal");
+  for _ in 0..ticks - 1 {
+    AnimationSystem.run_now(&world);
+    CorpseSystem.run_now(&world);
+    world.maintain();
+  }
+  assert!(world.is_alive(zombie), "Corpse should stay until it has faded out");
+  assert!(world.read_storage::<Renderable>().get(zombie).expect("Corpse should be drawn").alpha < 1.0, "Corpse should be fading");
+  assert!(world.read_storage::<Corpse>().get(alive).is_none(), "Living zombie should not become a corpse");
+
+  AnimationSystem.run_now(&world);
+  CorpseSystem.run_now(&world);
+  world.maintain();
+  assert!(!world.is_alive(zombie), "Faded corpse should be removed");
+  assert!(world.is_alive(alive), "Living zombie should stay");
+  assert_eq!(world.read_storage::<Corpse>().join().count(), 0);
+}